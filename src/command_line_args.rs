@@ -1,14 +1,186 @@
-use clap::Parser;
+use bfdbg::{
+    executor::{
+        cell_type::CellType,
+        eof_policy::EofPolicy,
+        executor_config::{DEFAULT_TAPE_LENGTH, ExecutorConfig, MAX_TAPE_LENGTH},
+        io_mode::IoMode,
+        overflow_policy::OverflowPolicy,
+        tape::TapeMode,
+    },
+    transpiler::target::Target,
+};
+use clap::{Parser, Subcommand};
+use std::time::Duration;
 
 /// A simple brainfuck interpreter
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// The file path of the brainfuck source code
-    #[arg(value_hint=clap::ValueHint::DirPath)]
-    pub path: String,
+    #[arg(value_hint=clap::ValueHint::DirPath, required_unless_present = "resume")]
+    pub path: Option<String>,
 
     /// Debug mode that allows debugging brainfuck code
     #[arg(short, long)]
     pub debug: bool,
+
+    /// Runs the program exactly as written instead of folding runs of commands and common loops
+    #[arg(long)]
+    pub no_optimize: bool,
+
+    /// Compiles the program into x86-64 machine code before running it
+    #[arg(long, conflicts_with = "no_optimize")]
+    pub jit: bool,
+
+    /// Counts how often every command runs and reports the hottest loops and commands
+    #[arg(long, conflicts_with_all = ["debug", "jit", "no_optimize"])]
+    pub profile: bool,
+
+    /// Records every step of the program into the file, one JSON object per line
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["debug", "jit", "profile"])]
+    pub trace: Option<String>,
+
+    /// Runs the program exactly as written and saves it into the file if it stops before finishing
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["debug", "jit", "profile", "trace"])]
+    pub snapshot: Option<String>,
+
+    /// Resumes the program saved in the file, skipping the input it had already read
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["path", "debug", "jit", "profile", "trace"]
+    )]
+    pub resume: Option<String>,
+
+    /// Stops the program after this many steps
+    #[arg(long, conflicts_with_all = ["debug", "jit"])]
+    pub max_steps: Option<u64>,
+
+    /// Stops the program after running for this many seconds
+    #[arg(long, value_parser = parse_timeout, conflicts_with_all = ["debug", "jit"])]
+    pub timeout: Option<Duration>,
+
+    /// The number of cells a growable or bidirectional tape may grow to
+    #[arg(long)]
+    pub max_cells: Option<usize>,
+
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Translates a brainfuck program into another language
+    Compile {
+        /// The file path of the brainfuck source code
+        #[arg(value_hint=clap::ValueHint::DirPath)]
+        path: String,
+
+        /// The language to translate the program into
+        #[arg(long, value_enum)]
+        target: Target,
+
+        /// The file to write the translated program to, instead of the standard output
+        #[arg(short, long)]
+        output: Option<String>,
+
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+    /// Runs a brainfuck program on several inputs and reports which commands never ran
+    Coverage {
+        /// The file path of the brainfuck source code
+        #[arg(value_hint=clap::ValueHint::DirPath)]
+        path: String,
+
+        /// A file to run the program on, can be given several times. Without any, the program runs
+        /// once on the standard input
+        #[arg(short, long = "input")]
+        inputs: Vec<String>,
+
+        /// The file to write the coverage to in the LCOV format
+        #[arg(long)]
+        lcov: Option<String>,
+
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+    /// Builds a brainfuck program into a static x86-64 Linux executable
+    #[command(mut_arg("cell_type", |arg| arg.default_value("u8")))]
+    Build {
+        /// The file path of the brainfuck source code
+        #[arg(value_hint=clap::ValueHint::DirPath)]
+        path: String,
+
+        /// The file to write the executable to
+        #[arg(short, long)]
+        output: String,
+
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+}
+
+/// The options deciding how the program behaves.
+#[derive(clap::Args)]
+pub struct ConfigArgs {
+    /// The type of every cell on the tape
+    #[arg(short, long, value_enum, default_value_t)]
+    pub cell_type: CellType,
+
+    /// What happens when a cell goes past the bounds of its type
+    #[arg(long, value_enum, default_value_t)]
+    pub overflow: OverflowPolicy,
+
+    /// The shape of the tape
+    #[arg(short, long, value_enum, default_value_t)]
+    pub tape: TapeMode,
+
+    /// The number of cells of a fixed or circular tape
+    #[arg(long, default_value_t = DEFAULT_TAPE_LENGTH, value_parser = parse_tape_length)]
+    pub tape_length: usize,
+
+    /// What the `,` command does once the input has run out
+    #[arg(long, value_enum, default_value_t)]
+    pub eof: EofPolicy,
+
+    /// How the `.` and `,` commands turn cells into bytes and back
+    #[arg(long, value_enum, default_value_t)]
+    pub io: IoMode,
+}
+
+impl ConfigArgs {
+    pub fn executor_config(&self) -> ExecutorConfig {
+        ExecutorConfig {
+            cell_type: self.cell_type,
+            overflow_policy: self.overflow,
+            tape_mode: self.tape,
+            tape_length: self.tape_length,
+            eof_policy: self.eof,
+            io_mode: self.io,
+            ..ExecutorConfig::default()
+        }
+    }
+}
+
+fn parse_timeout(seconds: &str) -> Result<Duration, String> {
+    let seconds: f64 = seconds.parse().map_err(|err| format!("{err}"))?;
+    Duration::try_from_secs_f64(seconds).map_err(|err| format!("{err}"))
+}
+
+fn parse_tape_length(cells: &str) -> Result<usize, String> {
+    let cells: usize = cells.parse().map_err(|err| format!("{err}"))?;
+    if cells > MAX_TAPE_LENGTH {
+        return Err(format!("a tape can have at most {MAX_TAPE_LENGTH} cells"));
+    }
+    Ok(cells)
 }
@@ -0,0 +1,63 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::executor::{
+    executor_command::ExecutorCommand, observer::Observer, observer_action::ObserverAction,
+};
+
+/// Pauses a program continued in the debugger at the next breakpoint, printing every command it
+/// reaches on the way. The command the program is continued from always runs, even if it is a
+/// breakpoint itself.
+pub struct BreakpointObserver<'a> {
+    breakpoints: &'a HashSet<usize>,
+    marks: &'a HashMap<usize, String>,
+    started: bool,
+}
+
+impl<'a> BreakpointObserver<'a> {
+    pub fn new(
+        breakpoints: &'a HashSet<usize>,
+        marks: &'a HashMap<usize, String>,
+    ) -> BreakpointObserver<'a> {
+        BreakpointObserver {
+            breakpoints,
+            marks,
+            started: false,
+        }
+    }
+}
+
+impl Observer for BreakpointObserver<'_> {
+    fn on_step(&mut self, pc: usize, command: &ExecutorCommand) -> ObserverAction {
+        if !self.started {
+            self.started = true;
+            return ObserverAction::Continue;
+        }
+
+        let breakpoint = self.breakpoints.contains(&pc);
+        describe_command(pc, command, self.marks.get(&pc), breakpoint);
+
+        if breakpoint {
+            ObserverAction::Pause
+        } else {
+            ObserverAction::Continue
+        }
+    }
+}
+
+/// Prints the command at the index together with its mark and whether it is a breakpoint.
+pub(crate) fn describe_command(
+    index: usize,
+    command: &ExecutorCommand,
+    mark: Option<&String>,
+    breakpoint: bool,
+) {
+    println!("The command at index {} is {}.", index, command);
+
+    if let Some(mark) = mark {
+        println!("It is marked as <{}>.", mark);
+    }
+
+    if breakpoint {
+        println!("It is a breakpoint.");
+    }
+}
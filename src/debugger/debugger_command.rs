@@ -14,7 +14,7 @@ pub enum DebuggerCommand {
 
     /// Prints the content of the cell at an index. The parameter is the index. If not provided,
     /// the data pointer will be used.
-    PrintCell(Option<usize>),
+    PrintCell(Option<isize>),
 
     /// Prints all instructions in the program.
     PrintAllInstructions,
@@ -45,12 +45,12 @@ pub enum DebuggerCommand {
 
     /// Lists 11 cells around an index. For example, if the index is 10, this command will list the
     /// cells from 5 to 15. If the index is not provided, the data pointer will be used.
-    ListCell(Option<usize>),
+    ListCell(Option<isize>),
 
     /// Lists n (specified by the first parameter) cells before and after an index (specified by the
     /// second parameter). For example, if `n = 5`, this command works the same as `ListCell(index)`.
     /// If the index is not provided, the data pointer will be used.
-    LongListCell(usize, Option<usize>),
+    LongListCell(usize, Option<isize>),
 
     /// Lists 11 cells around an index. For example, if the index is 10, this command will list the
     /// cells from 5 to 15. If the index is not provided, the data pointer will be used.
@@ -63,7 +63,7 @@ pub enum DebuggerCommand {
 
     /// Sets the value of a cell given its index. If the index is not provided, the data pointer
     /// will be used.
-    SetCell(Int, Option<usize>),
+    SetCell(Int, Option<isize>),
 
     /// Sets the value of a cell given its name.
     SetMarkedCell(Int, String), //implemented
@@ -80,7 +80,7 @@ pub enum DebuggerCommand {
 
     /// Marks an cell by its index. The first parameter is the name and the second parameter is the
     /// index. If the index is not provided, the data pointer will be used.
-    MarkCell(String, Option<usize>),
+    MarkCell(String, Option<isize>),
 
     /// Sets the program counter to an instruction given its index.
     Jump(usize),
@@ -89,7 +89,7 @@ pub enum DebuggerCommand {
     JumpMark(String),
 
     /// Sets the data pointer to a cell given its index.
-    JumpCell(usize),
+    JumpCell(isize),
 
     /// Sets the data pointer to a marked cell given its name.
     JumpMarkedCell(String),
@@ -139,7 +139,7 @@ impl TryFrom<String> for DebuggerCommand {
                     Ok(Self::PrintInstruction(index))
                 }
                 "pc" | "print_cell" => {
-                    let index = parse_optional_usize(&mut input)?;
+                    let index = parse_optional_isize(&mut input)?;
                     Ok(Self::PrintCell(index))
                 }
                 "pai" | "print_all_instructions" => {
@@ -175,12 +175,12 @@ impl TryFrom<String> for DebuggerCommand {
                     Ok(Self::LongListMarkedInstruction(length, mark))
                 }
                 "lc" | "list_cell" => {
-                    let index = parse_optional_usize(&mut input)?;
+                    let index = parse_optional_isize(&mut input)?;
                     Ok(Self::ListCell(index))
                 }
                 "llc" | "long_list_cell" => {
                     let length = parse_usize_value(&mut input, false)?;
-                    let index = parse_optional_usize(&mut input)?;
+                    let index = parse_optional_isize(&mut input)?;
                     Ok(Self::LongListCell(length, index))
                 }
                 "lmc" | "list_marked_cell" => {
@@ -194,7 +194,7 @@ impl TryFrom<String> for DebuggerCommand {
                 }
                 "sc" | "set_cell" => {
                     let value = parse_int_value(&mut input)?;
-                    let index = parse_optional_usize(&mut input)?;
+                    let index = parse_optional_isize(&mut input)?;
                     Ok(Self::SetCell(value, index))
                 }
                 "smc" | "set_marked_cell" => {
@@ -217,7 +217,7 @@ impl TryFrom<String> for DebuggerCommand {
                 }
                 "mc" | "mark_cell" => {
                     let mark = parse_string_value(&mut input, false)?;
-                    let index = parse_optional_usize(&mut input)?;
+                    let index = parse_optional_isize(&mut input)?;
                     Ok(Self::MarkCell(mark, index))
                 }
                 "j" | "jump" => {
@@ -229,7 +229,7 @@ impl TryFrom<String> for DebuggerCommand {
                     Ok(Self::JumpMark(mark))
                 }
                 "jc" | "jump_cell" => {
-                    let index = parse_usize_value(&mut input, true)?;
+                    let index = parse_isize_value(&mut input)?;
                     Ok(Self::JumpCell(index))
                 }
                 "jmc" | "jump_marked_cell" => {
@@ -326,6 +326,22 @@ fn parse_usize_value(
     }
 }
 
+fn parse_isize_value(input: &mut SplitWhitespace<'_>) -> Result<isize, DebuggerCommandParseError> {
+    if let Some(value) = input.next() {
+        if input.next().is_some() {
+            return Err(DebuggerCommandParseError::InvalidCommandFormat);
+        }
+
+        if let Ok(value) = value.parse() {
+            Ok(value)
+        } else {
+            Err(DebuggerCommandParseError::InvalidParameter)
+        }
+    } else {
+        Err(DebuggerCommandParseError::InvalidCommandFormat)
+    }
+}
+
 fn parse_string_value(
     input: &mut SplitWhitespace<'_>,
     last: bool,
@@ -377,3 +393,22 @@ fn parse_optional_usize(
         Ok(None)
     }
 }
+
+fn parse_optional_isize(
+    input: &mut SplitWhitespace<'_>,
+) -> Result<Option<isize>, DebuggerCommandParseError> {
+    let index = input.next();
+    if let Some(index) = index {
+        if input.next().is_some() {
+            return Err(DebuggerCommandParseError::InvalidCommandFormat);
+        }
+
+        if let Ok(index) = index.parse() {
+            Ok(Some(index))
+        } else {
+            Err(DebuggerCommandParseError::InvalidParameter)
+        }
+    } else {
+        Ok(None)
+    }
+}
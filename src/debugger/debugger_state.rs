@@ -5,13 +5,17 @@ use std::{
 };
 
 use super::{
-    debugger_command::DebuggerCommand, debugging_error::DebuggingError,
-    debugging_state::DebuggingState, parse_error::DebuggerCommandParseError,
+    breakpoint_observer::{BreakpointObserver, describe_command},
+    debugger_command::DebuggerCommand,
+    debugging_error::DebuggingError,
+    debugging_state::DebuggingState,
+    parse_error::DebuggerCommandParseError,
 };
 
 use crate::executor::{
     executing_state::ExecutionState,
     executor_command::ExecutorCommand,
+    executor_config::ExecutorConfig,
     executor_state::{ExecutorState, Int},
 };
 
@@ -21,17 +25,21 @@ pub struct DebuggerState {
     state: ExecutorState,
     i_marks: HashMap<String, usize>,
     i_marked_indices: HashMap<usize, String>,
-    c_marks: HashMap<String, usize>,
-    c_marked_indices: HashMap<usize, String>,
+    c_marks: HashMap<String, isize>,
+    c_marked_indices: HashMap<isize, String>,
     breakpoints: HashSet<usize>,
     jump_history: Vec<usize>,
-    jump_cell_history: Vec<usize>,
+    jump_cell_history: Vec<isize>,
 }
 
 impl DebuggerState {
-    pub fn new(commands: Vec<ExecutorCommand>, breakpoints: HashSet<usize>) -> Self {
+    pub fn new(
+        commands: Vec<ExecutorCommand>,
+        breakpoints: HashSet<usize>,
+        config: ExecutorConfig,
+    ) -> Self {
         Self {
-            state: ExecutorState::new(commands),
+            state: ExecutorState::with_config(commands, config),
             i_marks: HashMap::new(),
             i_marked_indices: HashMap::new(),
             c_marks: HashMap::new(),
@@ -101,20 +109,17 @@ impl DebuggerState {
             .get_command(index)
             .ok_or(DebuggingError::IndexOutOfBounds)?;
 
-        println!("The command at index {} is {}.", index, command);
-
-        if let Some(mark) = self.i_marked_indices.get(&index) {
-            println!("It is marked as <{}>.", mark);
-        }
-
-        if self.breakpoints.contains(&index) {
-            println!("It is a breakpoint.");
-        }
+        describe_command(
+            index,
+            &command,
+            self.i_marked_indices.get(&index),
+            self.breakpoints.contains(&index),
+        );
 
         Ok(DebuggingState::Running)
     }
 
-    fn print_cell(&self, index: Option<usize>) -> DebuggingResult {
+    fn print_cell(&self, index: Option<isize>) -> DebuggingResult {
         let index = if let Some(i) = index {
             i
         } else {
@@ -126,7 +131,12 @@ impl DebuggerState {
             .get_cell(index)
             .ok_or(DebuggingError::IndexOutOfBounds)?;
 
-        println!("The cell at index {} has the value {}.", index, value);
+        println!(
+            "The cell at index {} has the value {} ({}).",
+            index,
+            value,
+            self.state.get_config().cell_type
+        );
         if let Some(c) = char::from_u32(value as u32) {
             println!("The value is a valid character <{}>.", c);
         }
@@ -151,12 +161,12 @@ impl DebuggerState {
     }
 
     fn print_all_cells(&self) -> DebuggingResult {
-        let cells = self.state.get_cells();
+        let tape = self.state.get_tape();
 
-        println!("Currently, there are {} cells in total.", cells.len());
+        println!("Currently, the tape is {}.", tape);
 
-        for (index, cell) in cells.iter().enumerate() {
-            self.p_cell(index, cell);
+        for (index, cell) in tape.iter() {
+            self.p_cell(index, &cell);
         }
 
         Ok(DebuggingState::Running)
@@ -211,11 +221,11 @@ impl DebuggerState {
         }
     }
 
-    fn list_cell(&self, index: Option<usize>) -> DebuggingResult {
+    fn list_cell(&self, index: Option<isize>) -> DebuggingResult {
         self.long_list_cell(5, index)
     }
 
-    fn long_list_cell(&self, length: usize, index: Option<usize>) -> DebuggingResult {
+    fn long_list_cell(&self, length: usize, index: Option<isize>) -> DebuggingResult {
         let index = if let Some(i) = index {
             i
         } else {
@@ -226,17 +236,21 @@ impl DebuggerState {
             return Err(DebuggingError::IndexOutOfBounds);
         }
 
-        let array_len = self.state.get_array_len();
+        let tape = self.state.get_tape();
+        let array_len = tape.len();
+        let length = length as isize;
 
-        let lower_bound = index.saturating_sub(length);
-        let upper_bound = if index + length >= array_len {
+        let lower_bound = (index - length).max(tape.lowest_index());
+        let upper_bound = if index + length > tape.highest_index() {
             let singular = array_len == 1;
             println!(
-                "Currently, there {} only {array_len} cell{} in total!",
+                "Currently, there {} only {array_len} cell{} in total, from index {} to index {}!",
                 if singular { "is" } else { "are" },
-                if singular { "" } else { "s" }
+                if singular { "" } else { "s" },
+                tape.lowest_index(),
+                tape.highest_index()
             );
-            array_len - 1
+            tape.highest_index()
         } else {
             index + length
         };
@@ -260,13 +274,18 @@ impl DebuggerState {
         }
     }
 
-    fn set_cell(&mut self, value: Int, index: Option<usize>) -> DebuggingResult {
+    fn set_cell(&mut self, value: Int, index: Option<isize>) -> DebuggingResult {
         let index = if let Some(i) = index {
             i
         } else {
             self.state.get_pointer()
         };
 
+        let cell_type = self.state.get_config().cell_type;
+        if !cell_type.contains(value) {
+            return Err(DebuggingError::ValueOutOfRange(cell_type));
+        }
+
         if self.state.set_cell_value(index, value) {
             Ok(DebuggingState::Running)
         } else {
@@ -328,7 +347,7 @@ impl DebuggerState {
         }
     }
 
-    fn mark_cell(&mut self, mark: String, index: Option<usize>) -> DebuggingResult {
+    fn mark_cell(&mut self, mark: String, index: Option<isize>) -> DebuggingResult {
         let index = if let Some(i) = index {
             i
         } else {
@@ -365,7 +384,7 @@ impl DebuggerState {
         }
     }
 
-    fn jump_cell(&mut self, index: usize) -> DebuggingResult {
+    fn jump_cell(&mut self, index: isize) -> DebuggingResult {
         let current_pointer = self.state.get_pointer();
 
         if self.state.set_pointer(index) {
@@ -467,18 +486,14 @@ impl DebuggerState {
     }
 
     fn continue_to_breakpoint(&mut self) -> DebuggingResult {
-        loop {
-            match self.step() {
-                Ok(DebuggingState::Finished) => return Ok(DebuggingState::Finished),
-                Err(err) => {
-                    println!("{}", err);
-                    return Ok(DebuggingState::Finished);
-                }
-                _ => {}
-            }
+        let mut observer = BreakpointObserver::new(&self.breakpoints, &self.i_marked_indices);
 
-            if self.breakpoints.contains(&self.state.get_pc()) {
-                break Ok(DebuggingState::Paused);
+        match self.state.run_observed(&mut observer) {
+            Ok(ExecutionState::Running) => Ok(DebuggingState::Paused),
+            Ok(ExecutionState::Finished) => Ok(DebuggingState::Finished),
+            Err(err) => {
+                println!("{}", err);
+                Ok(DebuggingState::Finished)
             }
         }
     }
@@ -499,7 +514,7 @@ impl DebuggerState {
         println!();
     }
 
-    fn p_cell(&self, index: usize, cell: &Int) {
+    fn p_cell(&self, index: isize, cell: &Int) {
         print!("Position: {}, Value: {}", index, cell);
         if let Some(mark) = self.c_marked_indices.get(&index) {
             print!(", Mark: {}", mark);
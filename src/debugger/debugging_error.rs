@@ -1,5 +1,7 @@
 use derive_more::Display;
 
+use crate::executor::cell_type::CellType;
+
 #[derive(Display)]
 pub enum DebuggingError {
     #[display("The index is out of bounds!")]
@@ -12,4 +14,6 @@ pub enum DebuggingError {
     BreakpointNotFound,
     #[display("The instruction is invalid!")]
     InvalidInstruction,
+    #[display("The value does not fit in a cell of type {}!", _0)]
+    ValueOutOfRange(CellType),
 }
@@ -1,3 +1,4 @@
+pub mod breakpoint_observer;
 pub mod debugger_command;
 pub mod debugger_state;
 pub mod debugging_error;
@@ -0,0 +1,95 @@
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::{
+    event_executor::EventExecutor, executing_error::ExecutionError,
+    execution_error_kind::ExecutionErrorKind, execution_event::ExecutionEvent,
+    executor_command::ExecutorCommand, executor_config::ExecutorConfig,
+};
+
+/// The number of commands a program runs between two chances for other tasks to run.
+const YIELD_INTERVAL: u64 = 4096;
+
+/// The number of bytes read from the input at a time.
+const READ_SIZE: usize = 1024;
+
+/// Runs a brainfuck program on a tokio runtime, reading its input from `R` and writing its output
+/// to `W`. The program yields to the runtime regularly, so many programs can share it, and is
+/// cancelled by dropping the future of `run`, for example with `tokio::time::timeout`.
+///
+/// Unlike `ExecutorState`, the input is read ahead in blocks, so bytes after the last one the
+/// program reads may be consumed from `R`.
+pub struct AsyncExecutor<R, W> {
+    executor: EventExecutor,
+    input: R,
+    output: W,
+}
+
+impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> AsyncExecutor<R, W> {
+    pub fn new(
+        commands: Vec<ExecutorCommand>,
+        config: ExecutorConfig,
+        input: R,
+        output: W,
+    ) -> AsyncExecutor<R, W> {
+        AsyncExecutor {
+            executor: EventExecutor::new(commands, config),
+            input,
+            output,
+        }
+    }
+
+    /// Runs the program to its end, writing out everything it has output once it stops.
+    pub async fn run(&mut self) -> Result<(), ExecutionError> {
+        let result = self.run_events().await;
+        let flushed = self
+            .output
+            .flush()
+            .await
+            .map_err(|_err| ExecutionError::from(ExecutionErrorKind::OutputError));
+
+        result.and(flushed)
+    }
+
+    pub fn get_executor(&self) -> &EventExecutor {
+        &self.executor
+    }
+
+    /// Consumes the executor and gives back its input and output.
+    pub fn into_io(self) -> (R, W) {
+        (self.input, self.output)
+    }
+
+    async fn run_events(&mut self) -> Result<(), ExecutionError> {
+        let io_mode = self.executor.get_state().get_config().io_mode;
+        let mut buffer = [0; READ_SIZE];
+
+        loop {
+            match self.executor.resume_for(YIELD_INTERVAL) {
+                None => tokio::task::yield_now().await,
+                Some(ExecutionEvent::Output(value)) => {
+                    let mut encoded = [0; 4];
+                    let bytes = io_mode
+                        .encode(value, &mut encoded)
+                        .ok_or(ExecutionErrorKind::InvalidCharacter)?;
+                    self.output
+                        .write_all(bytes)
+                        .await
+                        .map_err(|_err| ExecutionErrorKind::OutputError)?;
+                }
+                Some(ExecutionEvent::NeedsInput) => {
+                    self.output
+                        .flush()
+                        .await
+                        .map_err(|_err| ExecutionErrorKind::OutputError)?;
+                    match self.input.read(&mut buffer).await {
+                        Ok(0) => self.executor.close_input(),
+                        Ok(length) => self.executor.provide_input(&buffer[..length]),
+                        Err(_) => return Err(ExecutionErrorKind::InputError.into()),
+                    }
+                }
+                Some(ExecutionEvent::Finished) => return Ok(()),
+                Some(ExecutionEvent::Error(err)) => return Err(err),
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use clap::ValueEnum;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use super::executor_state::Int;
+
+/// The width of a single cell on the tape. Every cell is stored as an `Int`, but its value is
+/// always kept within the range of the selected type.
+#[derive(
+    Clone, Copy, Debug, Default, Display, PartialEq, Eq, ValueEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum CellType {
+    #[display("u8")]
+    U8,
+    #[display("u16")]
+    U16,
+    #[display("u32")]
+    U32,
+    #[display("i32")]
+    I32,
+    #[display("i64")]
+    I64,
+    #[default]
+    #[display("i128")]
+    I128,
+}
+
+impl CellType {
+    pub fn min_value(&self) -> Int {
+        match self {
+            CellType::U8 | CellType::U16 | CellType::U32 => 0,
+            CellType::I32 => i32::MIN as Int,
+            CellType::I64 => i64::MIN as Int,
+            CellType::I128 => Int::MIN,
+        }
+    }
+
+    pub fn max_value(&self) -> Int {
+        match self {
+            CellType::U8 => u8::MAX as Int,
+            CellType::U16 => u16::MAX as Int,
+            CellType::U32 => u32::MAX as Int,
+            CellType::I32 => i32::MAX as Int,
+            CellType::I64 => i64::MAX as Int,
+            CellType::I128 => Int::MAX,
+        }
+    }
+
+    /// The number of bytes a cell of this type takes up in memory.
+    pub fn size(&self) -> usize {
+        match self {
+            CellType::U8 => 1,
+            CellType::U16 => 2,
+            CellType::U32 | CellType::I32 => 4,
+            CellType::I64 => 8,
+            CellType::I128 => 16,
+        }
+    }
+
+    pub fn contains(&self, value: Int) -> bool {
+        (self.min_value()..=self.max_value()).contains(&value)
+    }
+
+    /// Brings an arbitrary value back into the range of this type, modulo its width.
+    pub fn wrap(&self, value: Int) -> Int {
+        match self {
+            CellType::I128 => value,
+            _ => {
+                let min = self.min_value();
+                let span = self.max_value() - min + 1;
+                value.wrapping_sub(min).rem_euclid(span) + min
+            }
+        }
+    }
+
+    pub fn wrapping_add(&self, value: Int, delta: Int) -> Int {
+        self.wrap(value.wrapping_add(delta))
+    }
+
+    /// Brings an arbitrary value back into the range of this type by clamping it to its bounds.
+    pub fn saturate(&self, value: Int) -> Int {
+        value.clamp(self.min_value(), self.max_value())
+    }
+
+    pub fn saturating_add(&self, value: Int, delta: Int) -> Int {
+        self.saturate(value.saturating_add(delta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::executor::executor_state::Int;
+
+    use super::CellType;
+
+    const CELL_TYPES: [CellType; 6] = [
+        CellType::U8,
+        CellType::U16,
+        CellType::U32,
+        CellType::I32,
+        CellType::I64,
+        CellType::I128,
+    ];
+
+    #[test]
+    fn bounds_match_the_widths() {
+        for cell_type in CELL_TYPES {
+            let (min, max) = (cell_type.min_value(), cell_type.max_value());
+            let bits = cell_type.size() as u32 * 8;
+
+            assert!(cell_type.contains(min) && cell_type.contains(max));
+            if cell_type == CellType::I128 {
+                continue;
+            }
+            assert!(!cell_type.contains(min - 1) && !cell_type.contains(max + 1));
+            assert_eq!(max - min, (1 << bits) - 1, "{cell_type}");
+        }
+    }
+
+    #[test]
+    fn values_wrap_around_at_the_bounds() {
+        for cell_type in CELL_TYPES {
+            let (min, max) = (cell_type.min_value(), cell_type.max_value());
+
+            assert_eq!(cell_type.wrapping_add(max, 1), min, "{cell_type}");
+            assert_eq!(cell_type.wrapping_add(min, -1), max, "{cell_type}");
+            assert_eq!(cell_type.wrapping_add(max, 3), min + 2, "{cell_type}");
+            assert_eq!(cell_type.wrapping_add(min, -3), max - 2, "{cell_type}");
+            assert_eq!(cell_type.wrapping_add(max, -1), max - 1, "{cell_type}");
+        }
+
+        assert_eq!(CellType::U8.wrap(-1), 255);
+        assert_eq!(CellType::U8.wrap(256 * 7 + 5), 5);
+        assert_eq!(CellType::I32.wrap(i32::MAX as Int + 1), i32::MIN as Int);
+        assert_eq!(CellType::U32.wrap(-(u32::MAX as Int)), 1);
+    }
+
+    #[test]
+    fn values_saturate_at_the_bounds() {
+        for cell_type in CELL_TYPES {
+            let (min, max) = (cell_type.min_value(), cell_type.max_value());
+
+            assert_eq!(cell_type.saturating_add(max, 1), max, "{cell_type}");
+            assert_eq!(cell_type.saturating_add(min, -1), min, "{cell_type}");
+            assert_eq!(cell_type.saturating_add(max, Int::MAX), max, "{cell_type}");
+            assert_eq!(cell_type.saturating_add(min, Int::MIN), min, "{cell_type}");
+            assert_eq!(cell_type.saturating_add(max, -1), max - 1, "{cell_type}");
+        }
+
+        assert_eq!(CellType::U8.saturate(-1), 0);
+        assert_eq!(CellType::U16.saturate(70000), u16::MAX as Int);
+    }
+}
@@ -0,0 +1,24 @@
+use clap::ValueEnum;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+/// What the `,` command does once the input has run out.
+#[derive(
+    Clone, Copy, Debug, Default, Display, PartialEq, Eq, ValueEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum EofPolicy {
+    /// Stores 0 in the cell.
+    #[display("zero")]
+    Zero,
+    /// Stores -1 in the cell, which is the maximum value of an unsigned cell.
+    #[display("minus-one")]
+    MinusOne,
+    /// Leaves the cell unchanged.
+    #[display("unchanged")]
+    Unchanged,
+    /// Stops the program with an input error.
+    #[default]
+    #[display("error")]
+    Error,
+}
@@ -0,0 +1,31 @@
+use std::fmt;
+
+use crate::source_position::SourcePosition;
+
+use super::executor_state::Int;
+
+/// Where a program was when it failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// The index of the command that failed, counted in the commands of the program as written.
+    pub pc: usize,
+    /// Where the command that failed is in the source code, if that is known.
+    pub position: Option<SourcePosition>,
+    pub pointer: isize,
+    /// The value of the cell the data pointer was at.
+    pub cell: Int,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at command {}", self.pc)?;
+        if let Some(position) = self.position {
+            write!(f, " (line {}, column {})", position.line, position.column)?;
+        }
+        write!(
+            f,
+            ", with the data pointer at cell {} holding {}",
+            self.pointer, self.cell
+        )
+    }
+}
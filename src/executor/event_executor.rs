@@ -0,0 +1,257 @@
+use std::io::{self, Sink};
+
+use super::{
+    executing_state::ExecutionState,
+    execution_event::ExecutionEvent,
+    executor_command::ExecutorCommand,
+    executor_config::ExecutorConfig,
+    executor_state::{ExecutorState, Int},
+    input_buffer::InputBuffer,
+    observer::Observer,
+};
+
+/// Runs a brainfuck program driven by the host, which calls `resume` to run it until the next
+/// event and hands over input with `provide_input` whenever it is asked for. Nothing ever blocks,
+/// so programs can run inside an event loop without a thread of their own.
+///
+/// A program that runs on without reading or writing anything keeps `resume` from returning,
+/// use `resume_for` or set `max_steps` or `timeout` in the configuration to bound it.
+pub struct EventExecutor {
+    state: ExecutorState<InputBuffer, Sink>,
+}
+
+impl EventExecutor {
+    pub fn new(commands: Vec<ExecutorCommand>, config: ExecutorConfig) -> EventExecutor {
+        EventExecutor {
+            state: ExecutorState::with_io(commands, config, InputBuffer::default(), io::sink()),
+        }
+    }
+
+    /// Runs the program until it asks for input, writes output, finishes or fails. Resuming a
+    /// program that asked for input without providing any asks again, resuming a finished or
+    /// failed program gives back the same event again.
+    pub fn resume(&mut self) -> ExecutionEvent {
+        loop {
+            if let Some(event) = self.resume_for(u64::MAX) {
+                return event;
+            }
+        }
+    }
+
+    /// Runs the program like `resume`, but for no more than `steps` commands. Gives back `None`
+    /// if the program was still running after them, so the host can get on with other work
+    /// before resuming it again.
+    pub fn resume_for(&mut self, steps: u64) -> Option<ExecutionEvent> {
+        let io_mode = self.state.get_config().io_mode;
+
+        for _ in 0..steps {
+            if let Some(ExecutorCommand::Input) = self.state.get_command(self.state.get_pc())
+                && !self.state.get_input().is_ready(io_mode)
+            {
+                return Some(ExecutionEvent::NeedsInput);
+            }
+
+            let mut output = OutputObserver::default();
+            match self.state.execute_once_observed(&mut output) {
+                Ok(ExecutionState::Running) => {
+                    if let Some(value) = output.value {
+                        return Some(ExecutionEvent::Output(value));
+                    }
+                }
+                Ok(ExecutionState::Finished) => return Some(ExecutionEvent::Finished),
+                Err(err) => return Some(ExecutionEvent::Error(err)),
+            }
+        }
+
+        None
+    }
+
+    /// Hands more input over to the program.
+    pub fn provide_input(&mut self, bytes: &[u8]) {
+        self.state.get_input_mut().push(bytes);
+    }
+
+    /// Tells the program that there is no more input, so the EOF policy applies once the input
+    /// provided so far has been read.
+    pub fn close_input(&mut self) {
+        self.state.get_input_mut().close();
+    }
+
+    pub fn get_state(&self) -> &ExecutorState<InputBuffer, Sink> {
+        &self.state
+    }
+}
+
+/// Remembers the value written by the step it watched.
+#[derive(Default)]
+struct OutputObserver {
+    value: Option<Int>,
+}
+
+impl Observer for OutputObserver {
+    fn on_output(&mut self, value: Int) {
+        self.value = Some(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::{
+            cell_type::CellType, eof_policy::EofPolicy, execution_error_kind::ExecutionErrorKind,
+            execution_event::ExecutionEvent, executor_config::ExecutorConfig, io_mode::IoMode,
+        },
+        parsing_src::translate_into_commands,
+    };
+
+    use super::EventExecutor;
+
+    fn start(source: &str, config: ExecutorConfig) -> EventExecutor {
+        let (commands, _) = translate_into_commands(source, false).unwrap();
+        EventExecutor::new(commands, config)
+    }
+
+    fn error_kind(event: ExecutionEvent) -> Option<ExecutionErrorKind> {
+        match event {
+            ExecutionEvent::Error(err) => Some(err.get_kind()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn input_is_asked_for_until_it_is_provided() {
+        let mut executor = start(",.", ExecutorConfig::default());
+
+        assert_eq!(executor.resume(), ExecutionEvent::NeedsInput);
+        assert_eq!(executor.resume(), ExecutionEvent::NeedsInput);
+        assert_eq!(executor.get_state().get_pc(), 0);
+
+        executor.provide_input(b"a");
+        assert_eq!(executor.resume(), ExecutionEvent::Output('a' as i128));
+        assert_eq!(executor.resume(), ExecutionEvent::Finished);
+    }
+
+    #[test]
+    fn split_characters_wait_for_the_rest() {
+        let mut executor = start(",.,.", ExecutorConfig::default());
+
+        executor.provide_input(&[0xc3]);
+        assert_eq!(executor.resume(), ExecutionEvent::NeedsInput);
+        executor.provide_input(&[0xa9]);
+        assert_eq!(executor.resume(), ExecutionEvent::Output('é' as i128));
+
+        for byte in "🦀".bytes() {
+            assert_eq!(executor.resume(), ExecutionEvent::NeedsInput);
+            executor.provide_input(&[byte]);
+        }
+        assert_eq!(executor.resume(), ExecutionEvent::Output('🦀' as i128));
+        assert_eq!(executor.resume(), ExecutionEvent::Finished);
+    }
+
+    #[test]
+    fn split_bytes_are_read_right_away() {
+        let config = ExecutorConfig {
+            io_mode: IoMode::Byte,
+            ..ExecutorConfig::default()
+        };
+        let mut executor = start(",.", config);
+
+        executor.provide_input(&[0xc3]);
+        assert_eq!(executor.resume(), ExecutionEvent::Output(0xc3));
+    }
+
+    #[test]
+    fn closing_the_input_applies_the_eof_policy() {
+        for (eof_policy, expected) in [
+            (EofPolicy::Zero, ExecutionEvent::Output(0)),
+            (EofPolicy::MinusOne, ExecutionEvent::Output(255)),
+            (EofPolicy::Unchanged, ExecutionEvent::Output(1)),
+        ] {
+            let config = ExecutorConfig {
+                cell_type: CellType::U8,
+                eof_policy,
+                io_mode: IoMode::Byte,
+                ..ExecutorConfig::default()
+            };
+            let mut executor = start("+,.", config);
+
+            assert_eq!(executor.resume(), ExecutionEvent::NeedsInput);
+            executor.close_input();
+            assert_eq!(executor.resume(), expected, "{}", eof_policy);
+            assert_eq!(
+                executor.resume(),
+                ExecutionEvent::Finished,
+                "{}",
+                eof_policy
+            );
+        }
+
+        let mut executor = start("+,.", ExecutorConfig::default());
+        executor.close_input();
+        assert_eq!(
+            error_kind(executor.resume()),
+            Some(ExecutionErrorKind::InputError)
+        );
+    }
+
+    #[test]
+    fn input_provided_before_closing_is_read_first() {
+        let config = ExecutorConfig {
+            eof_policy: EofPolicy::Zero,
+            ..ExecutorConfig::default()
+        };
+        let mut executor = start(",.,.", config);
+
+        executor.provide_input(b"a");
+        executor.close_input();
+        assert_eq!(executor.resume(), ExecutionEvent::Output('a' as i128));
+        assert_eq!(executor.resume(), ExecutionEvent::Output(0));
+
+        // The rest of a character never comes once the input is closed.
+        let mut executor = start(",.", config);
+        executor.provide_input(&[0xc3]);
+        executor.close_input();
+        assert_eq!(
+            error_kind(executor.resume()),
+            Some(ExecutionErrorKind::InvalidInput)
+        );
+    }
+
+    #[test]
+    fn outputs_come_in_order() {
+        let mut executor = start("+.+.,.+.", ExecutorConfig::default());
+        executor.provide_input(b"x");
+
+        for value in [1, 2, 'x' as i128, 'y' as i128] {
+            assert_eq!(executor.resume(), ExecutionEvent::Output(value));
+        }
+        assert_eq!(executor.resume(), ExecutionEvent::Finished);
+    }
+
+    #[test]
+    fn finished_and_failed_programs_stay_where_they_stopped() {
+        let mut executor = start("+.", ExecutorConfig::default());
+        assert_eq!(executor.resume(), ExecutionEvent::Output(1));
+        for _ in 0..2 {
+            assert_eq!(executor.resume(), ExecutionEvent::Finished);
+        }
+
+        let mut executor = start("+<", ExecutorConfig::default());
+        for _ in 0..2 {
+            assert_eq!(
+                error_kind(executor.resume()),
+                Some(ExecutionErrorKind::TooFarLeft)
+            );
+            assert_eq!(executor.get_state().get_pc(), 1);
+        }
+    }
+
+    #[test]
+    fn resuming_for_a_few_steps_gives_back_control() {
+        let mut executor = start("+[]", ExecutorConfig::default());
+
+        assert_eq!(executor.resume_for(100), None);
+        assert_eq!(executor.resume_for(100), None);
+        assert_eq!(executor.get_state().get_steps(), 200);
+    }
+}
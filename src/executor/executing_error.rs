@@ -1,19 +1,53 @@
-use derive_more::Display;
-
-#[derive(Display)]
-pub enum ExecutionError {
-    #[display("Index Error: The program counter is out of range.")]
-    ProgramCounterOutOfRange,
-    #[display("Index Error: You have gone too far to the left!")]
-    TooFarLeft,
-    #[display("Index Error: You have gone too far to the right!")]
-    TooFarRight,
-    #[display("Overflow Error: The number in the cell has reached its maximum!")]
-    Overflow,
-    #[display("Overflow Error: The number in the cell has reached its minimum!")]
-    Underflow,
-    #[display("Value Error: The value in the cell is not a valid Unicode character!")]
-    InvalidCharacter,
-    #[display("IO Error: Unable to get character input!")]
-    InputError,
+use std::{error::Error, fmt};
+
+use crate::source_position::SourcePosition;
+
+use super::{error_context::ErrorContext, execution_error_kind::ExecutionErrorKind};
+
+/// The error a program failed with, together with where it was when it failed if that is known.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExecutionError {
+    kind: ExecutionErrorKind,
+    context: Option<ErrorContext>,
 }
+
+impl ExecutionError {
+    pub fn new(kind: ExecutionErrorKind, context: Option<ErrorContext>) -> ExecutionError {
+        ExecutionError { kind, context }
+    }
+
+    pub fn get_kind(&self) -> ExecutionErrorKind {
+        self.kind
+    }
+
+    pub fn get_context(&self) -> Option<&ErrorContext> {
+        self.context.as_ref()
+    }
+
+    /// Looks up where the command that failed is in the source code, given the positions of all
+    /// commands as found by `parsing_src::source_positions`.
+    pub fn locate(mut self, positions: &[SourcePosition]) -> ExecutionError {
+        if let Some(context) = &mut self.context {
+            context.position = positions.get(context.pc).copied();
+        }
+        self
+    }
+}
+
+impl From<ExecutionErrorKind> for ExecutionError {
+    fn from(kind: ExecutionErrorKind) -> ExecutionError {
+        ExecutionError::new(kind, None)
+    }
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(context) = &self.context {
+            write!(f, "\n  {}", context)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ExecutionError {}
@@ -0,0 +1,32 @@
+use derive_more::Display;
+
+/// What went wrong when a program failed, see `ExecutionError` for where it happened.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum ExecutionErrorKind {
+    #[display("Index Error: The program counter is out of range.")]
+    ProgramCounterOutOfRange,
+    #[display("Index Error: You have gone too far to the left!")]
+    TooFarLeft,
+    #[display("Index Error: You have gone too far to the right!")]
+    TooFarRight,
+    #[display("Overflow Error: The number in the cell has reached its maximum!")]
+    Overflow,
+    #[display("Overflow Error: The number in the cell has reached its minimum!")]
+    Underflow,
+    #[display("Value Error: The value in the cell is not a valid Unicode character!")]
+    InvalidCharacter,
+    #[display("Value Error: The input is not a valid Unicode character!")]
+    InvalidInput,
+    #[display("IO Error: Unable to get character input!")]
+    InputError,
+    #[display("IO Error: Unable to write the output!")]
+    OutputError,
+    #[display("IO Error: Unable to write the trace!")]
+    TraceError,
+    #[display("Limit Error: The program has run for {steps} steps without finishing!")]
+    StepLimitExceeded { steps: u64 },
+    #[display("Limit Error: The program has run out of time after {steps} steps!")]
+    Timeout { steps: u64 },
+    #[display("Memory Error: The tape can not grow past {cells} cells!")]
+    TapeLimitExceeded { cells: usize },
+}
@@ -0,0 +1,12 @@
+use super::{executing_error::ExecutionError, executor_state::Int};
+
+/// What a program run by `EventExecutor::resume` stopped for.
+#[derive(Debug, PartialEq)]
+pub enum ExecutionEvent {
+    /// A `,` command is waiting for more input, see `EventExecutor::provide_input`.
+    NeedsInput,
+    /// A `.` command wrote the value, encode it with `IoMode::encode` to get its bytes.
+    Output(Int),
+    Finished,
+    Error(ExecutionError),
+}
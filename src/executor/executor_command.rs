@@ -1,6 +1,7 @@
 use derive_more::Display;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Display)]
+#[derive(Clone, Debug, Display, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExecutorCommand {
     MoveRight,
     MoveLeft,
@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    cell_type::CellType, eof_policy::EofPolicy, execution_error_kind::ExecutionErrorKind,
+    executor_state::Int, io_mode::IoMode, overflow_policy::OverflowPolicy, tape::TapeMode,
+};
+
+/// The number of cells of a fixed or circular tape, unless configured otherwise.
+pub const DEFAULT_TAPE_LENGTH: usize = 30000;
+
+/// The largest number of cells a fixed or circular tape can have, beyond which its cells could not
+/// be addressed in memory.
+pub const MAX_TAPE_LENGTH: usize = isize::MAX as usize / size_of::<Int>();
+
+/// Options that change how a brainfuck program behaves while it is being executed.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ExecutorConfig {
+    pub cell_type: CellType,
+    pub overflow_policy: OverflowPolicy,
+    pub tape_mode: TapeMode,
+    /// The number of cells of a fixed or circular tape, at most `MAX_TAPE_LENGTH`.
+    pub tape_length: usize,
+    pub eof_policy: EofPolicy,
+    pub io_mode: IoMode,
+    /// The number of steps the program may run for before it is stopped. Only the interpreters
+    /// enforce this limit, the JIT and the WebAssembly backend reject it.
+    pub max_steps: Option<u64>,
+    /// How long the program may run for before it is stopped, counted from its first step. Only
+    /// the interpreters enforce this limit, the JIT and the WebAssembly backend reject it.
+    pub timeout: Option<Duration>,
+    /// The number of cells a growable or bidirectional tape may grow to. Only the interpreters and
+    /// the JIT enforce this limit, see `check_tape` for fixed and circular tapes.
+    pub max_cells: Option<usize>,
+}
+
+impl ExecutorConfig {
+    /// Checks that a fixed or circular tape, which has all of its cells from the start, has no
+    /// more cells than `max_cells`.
+    pub fn check_tape(&self) -> Result<(), ExecutionErrorKind> {
+        let fixed = matches!(self.tape_mode, TapeMode::Fixed | TapeMode::Circular);
+        match self.max_cells {
+            Some(max_cells) if fixed && self.tape_length.max(1) > max_cells => {
+                Err(ExecutionErrorKind::TapeLimitExceeded { cells: max_cells })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        ExecutorConfig {
+            cell_type: CellType::default(),
+            overflow_policy: OverflowPolicy::default(),
+            tape_mode: TapeMode::default(),
+            tape_length: DEFAULT_TAPE_LENGTH,
+            eof_policy: EofPolicy::default(),
+            io_mode: IoMode::default(),
+            max_steps: None,
+            timeout: None,
+            max_cells: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::executor::{execution_error_kind::ExecutionErrorKind, tape::TapeMode};
+
+    use super::ExecutorConfig;
+
+    fn config(tape_mode: TapeMode, tape_length: usize, max_cells: Option<usize>) -> ExecutorConfig {
+        ExecutorConfig {
+            tape_mode,
+            tape_length,
+            max_cells,
+            ..ExecutorConfig::default()
+        }
+    }
+
+    #[test]
+    fn fixed_tapes_have_to_fit_into_max_cells() {
+        for tape_mode in [TapeMode::Fixed, TapeMode::Circular] {
+            assert_eq!(config(tape_mode, 8, None).check_tape(), Ok(()));
+            assert_eq!(config(tape_mode, 8, Some(8)).check_tape(), Ok(()));
+            assert_eq!(
+                config(tape_mode, 9, Some(8)).check_tape(),
+                Err(ExecutionErrorKind::TapeLimitExceeded { cells: 8 })
+            );
+            // A tape always has at least one cell.
+            assert_eq!(
+                config(tape_mode, 0, Some(0)).check_tape(),
+                Err(ExecutionErrorKind::TapeLimitExceeded { cells: 0 })
+            );
+        }
+    }
+
+    #[test]
+    fn growing_tapes_are_limited_while_they_grow() {
+        for tape_mode in [TapeMode::Growable, TapeMode::Bidirectional] {
+            assert_eq!(config(tape_mode, 30000, Some(1)).check_tape(), Ok(()));
+        }
+    }
+}
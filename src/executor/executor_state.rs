@@ -1,87 +1,209 @@
-use std::io::{self, BufReader, Read};
-
-use crate::executor::{executing_state::ExecutionState, executor_command::ExecutorCommand};
-
-use super::executing_error::ExecutionError;
+use std::{
+    io::{self, ErrorKind, Read, Stdin, Stdout, Write},
+    time::Instant,
+};
+
+use crate::executor::{
+    eof_policy::EofPolicy, executing_state::ExecutionState, executor_command::ExecutorCommand,
+    executor_config::ExecutorConfig, observer::Observer, observer_action::ObserverAction,
+    overflow_policy::OverflowPolicy, snapshot::Snapshot, tape::Tape,
+};
+
+use super::{
+    error_context::ErrorContext, executing_error::ExecutionError,
+    execution_error_kind::ExecutionErrorKind,
+};
 
 pub type Int = i128;
 
 pub type ExecutionResult = Result<ExecutionState, ExecutionError>;
 
-const INITIAL_SIZE: usize = 32;
-
-pub struct ExecutorState {
-    pointer: usize,
-    array: Vec<Int>,
+/// The result of a single command, which is turned into an `ExecutionResult` once it is known
+/// where the program was.
+pub(crate) type CommandResult = Result<ExecutionState, ExecutionErrorKind>;
+
+/// The number of steps between two looks at the clock while a timeout is set.
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// The state of a running brainfuck program. The program reads its input from `R` and writes its
+/// output to `W`, which are the standard input and output unless specified otherwise.
+///
+/// The input is read one byte at a time for every `,` command and nothing is read ahead, so the
+/// bytes after it stay in `R`. Wrap unbuffered readers like files in a `BufReader` before passing
+/// them in.
+pub struct ExecutorState<R = Stdin, W = Stdout> {
+    pointer: isize,
+    tape: Tape,
     pc: usize,
     commands: Vec<ExecutorCommand>,
+    config: ExecutorConfig,
+    input: R,
+    output: W,
+    steps: u64,
+    started: Option<Instant>,
+    bytes_read: u64,
 }
 
 impl ExecutorState {
     pub fn new(commands: Vec<ExecutorCommand>) -> ExecutorState {
-        let mut array = Vec::with_capacity(INITIAL_SIZE);
-        array.push(0);
+        Self::with_config(commands, ExecutorConfig::default())
+    }
+
+    pub fn with_config(commands: Vec<ExecutorCommand>, config: ExecutorConfig) -> ExecutorState {
+        Self::with_io(commands, config, io::stdin(), io::stdout())
+    }
+}
 
+impl<R: Read, W: Write> ExecutorState<R, W> {
+    pub fn with_io(
+        commands: Vec<ExecutorCommand>,
+        config: ExecutorConfig,
+        input: R,
+        output: W,
+    ) -> ExecutorState<R, W> {
         ExecutorState {
             pointer: 0,
-            array,
+            tape: Tape::with_max_cells(config.tape_mode, config.tape_length, config.max_cells),
             pc: 0, // program counter
             commands,
+            config,
+            input,
+            output,
+            steps: 0,
+            started: None,
+            bytes_read: 0,
         }
     }
 
-    pub fn execute_once(&mut self) -> ExecutionResult {
-        if self.pc >= self.commands.len() {
-            return Ok(ExecutionState::Finished);
+    /// Resumes the program saved in the snapshot. The input has to start right after the bytes
+    /// the program had already read, see `Snapshot::get_bytes_read`.
+    pub fn from_snapshot(snapshot: Snapshot, input: R, output: W) -> ExecutorState<R, W> {
+        let mut tape = snapshot.tape;
+        tape.set_max_cells(snapshot.config.max_cells);
+
+        ExecutorState {
+            pointer: snapshot.pointer,
+            tape,
+            pc: snapshot.pc,
+            commands: snapshot.commands,
+            config: snapshot.config,
+            input,
+            output,
+            steps: snapshot.steps,
+            started: None,
+            bytes_read: snapshot.bytes_read,
         }
+    }
 
-        let current_cmd = self.commands[self.pc].clone();
+    pub fn execute_once(&mut self) -> ExecutionResult {
+        self.execute_once_observed(&mut ())
+    }
 
-        self.execute_command(current_cmd)?;
-        self.increment_pc();
+    /// Runs the next command like `execute_once`, telling the observer about everything it does.
+    /// If the observer pauses the program, the command does not run and `Running` is given back.
+    pub fn execute_once_observed(&mut self, observer: &mut dyn Observer) -> ExecutionResult {
+        if self.observe_step(observer) == ObserverAction::Pause {
+            return Ok(ExecutionState::Running);
+        }
 
-        Ok(ExecutionState::Running)
+        self.step(observer)
     }
 
-    pub fn execute_command(&mut self, command: ExecutorCommand) -> Result<usize, ExecutionError> {
-        self.check_state_valid()?;
+    /// Runs the program until it finishes, fails or the observer pauses it, telling the observer
+    /// about everything it does. `Running` is given back if the observer paused the program.
+    pub fn run_observed(&mut self, observer: &mut dyn Observer) -> ExecutionResult {
+        loop {
+            if self.observe_step(observer) == ObserverAction::Pause {
+                return Ok(ExecutionState::Running);
+            }
 
-        match command {
-            ExecutorCommand::MoveRight => self.move_to_the_right()?,
-            ExecutorCommand::MoveLeft => self.move_to_the_left()?,
-            ExecutorCommand::Increment => self.increment()?,
-            ExecutorCommand::Decrement => self.decrement()?,
-            ExecutorCommand::Input => self.input()?,
-            ExecutorCommand::Output => self.output()?,
-            ExecutorCommand::JumpForward(pos) => self.jump_forward(pos)?,
-            ExecutorCommand::JumpBack(pos) => self.jump_back(pos)?,
-        };
+            if let ExecutionState::Finished = self.step(observer)? {
+                return Ok(ExecutionState::Finished);
+            }
+        }
+    }
 
-        Ok(self.pc)
+    pub fn execute_command(&mut self, command: ExecutorCommand) -> Result<usize, ExecutionError> {
+        self.execute_command_observed(command, &mut ())
+            .map_err(|kind| self.error_at(kind, self.pc))
     }
 
     pub fn increment_pc(&mut self) {
         self.pc += 1;
     }
 
-    pub fn get_cells(&self) -> &Vec<Int> {
-        &self.array
+    pub fn get_tape(&self) -> &Tape {
+        &self.tape
     }
 
     pub fn get_commands(&self) -> &Vec<ExecutorCommand> {
         &self.commands
     }
 
+    pub fn get_config(&self) -> &ExecutorConfig {
+        &self.config
+    }
+
+    pub fn get_input(&self) -> &R {
+        &self.input
+    }
+
+    pub fn get_input_mut(&mut self) -> &mut R {
+        &mut self.input
+    }
+
+    pub fn get_output(&self) -> &W {
+        &self.output
+    }
+
+    pub fn get_output_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+
+    /// Consumes the state and gives back its input and output.
+    pub fn into_io(self) -> (R, W) {
+        (self.input, self.output)
+    }
+
+    /// Saves the program as it is now, so it can be resumed later.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            commands: self.commands.clone(),
+            config: self.config,
+            tape: self.tape.clone(),
+            pointer: self.pointer,
+            pc: self.pc,
+            steps: self.steps,
+            bytes_read: self.bytes_read,
+            positions: Vec::new(),
+        }
+    }
+
+    /// Writes out everything the program has output so far.
+    pub fn flush(&mut self) -> Result<(), ExecutionError> {
+        Ok(self.flush_output()?)
+    }
+
+    /// The number of steps the program has run for.
+    pub fn get_steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// The number of bytes the program has read from its input.
+    pub fn get_bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
     pub fn get_pc(&self) -> usize {
         self.pc
     }
 
-    pub fn get_pointer(&self) -> usize {
+    pub fn get_pointer(&self) -> isize {
         self.pointer
     }
 
-    pub fn get_cell(&self, index: usize) -> Option<Int> {
-        self.array.get(index).copied()
+    pub fn get_cell(&self, index: isize) -> Option<Int> {
+        self.tape.get(index)
     }
 
     pub fn get_command(&self, index: usize) -> Option<ExecutorCommand> {
@@ -89,7 +211,7 @@ impl ExecutorState {
     }
 
     pub fn get_array_len(&self) -> usize {
-        self.array.len()
+        self.tape.len()
     }
 
     pub fn get_commands_len(&self) -> usize {
@@ -105,7 +227,7 @@ impl ExecutorState {
         }
     }
 
-    pub fn set_pointer(&mut self, index: usize) -> bool {
+    pub fn set_pointer(&mut self, index: isize) -> bool {
         if self.validate_cell_index(index) {
             self.pointer = index;
             true
@@ -114,110 +236,668 @@ impl ExecutorState {
         }
     }
 
-    pub fn set_cell_value(&mut self, index: usize, value: Int) -> bool {
-        if self.validate_cell_index(index) {
-            self.array[index] = value;
-            true
-        } else {
-            false
-        }
+    pub fn set_cell_value(&mut self, index: isize, value: Int) -> bool {
+        self.config.cell_type.contains(value) && self.tape.set(index, value)
     }
 
     pub fn validate_command_index(&self, index: usize) -> bool {
         (0..self.commands.len()).contains(&index)
     }
 
-    pub fn validate_cell_index(&self, index: usize) -> bool {
-        (0..self.array.len()).contains(&index)
+    pub fn validate_cell_index(&self, index: isize) -> bool {
+        self.tape.contains(index)
     }
 
-    fn check_state_valid(&self) -> Result<(), ExecutionError> {
-        if self.pc >= self.commands.len() {
-            Err(ExecutionError::ProgramCounterOutOfRange)
-        } else if self.pointer >= self.array.len() {
-            Err(ExecutionError::TooFarRight)
-        } else {
-            Ok(())
+    fn observe_step(&mut self, observer: &mut dyn Observer) -> ObserverAction {
+        match self.commands.get(self.pc) {
+            Some(command) => observer.on_step(self.pc, command),
+            None => ObserverAction::Continue,
         }
     }
 
-    fn move_to_the_right(&mut self) -> ExecutionResult {
-        self.pointer += 1;
+    fn step(&mut self, observer: &mut dyn Observer) -> ExecutionResult {
+        self.run_step(observer)
+            .map_err(|kind| self.error_at(kind, self.pc))
+    }
 
-        if self.pointer >= self.array.len() {
-            self.array.push(0);
+    fn run_step(&mut self, observer: &mut dyn Observer) -> CommandResult {
+        if self.pc >= self.commands.len() {
+            self.flush_output()?;
+            return Ok(ExecutionState::Finished);
         }
 
+        self.count_steps(1)?;
+        let current_cmd = self.commands[self.pc].clone();
+
+        self.execute_command_observed(current_cmd, observer)?;
+        self.increment_pc();
+
         Ok(ExecutionState::Running)
     }
 
-    fn move_to_the_left(&mut self) -> ExecutionResult {
-        if self.pointer == 0 {
-            Err(ExecutionError::TooFarLeft)
-        } else {
-            self.pointer -= 1;
-            Ok(ExecutionState::Running)
-        }
+    fn execute_command_observed(
+        &mut self,
+        command: ExecutorCommand,
+        observer: &mut dyn Observer,
+    ) -> Result<usize, ExecutionErrorKind> {
+        self.check_state_valid()?;
+
+        match command {
+            ExecutorCommand::MoveRight => self.move_pointer(1, observer)?,
+            ExecutorCommand::MoveLeft => self.move_pointer(-1, observer)?,
+            ExecutorCommand::Increment => self.add_to_cell(1, observer)?,
+            ExecutorCommand::Decrement => self.add_to_cell(-1, observer)?,
+            ExecutorCommand::Input => self.input(observer)?,
+            ExecutorCommand::Output => self.output(observer)?,
+            ExecutorCommand::JumpForward(pos) => self.jump_forward(pos)?,
+            ExecutorCommand::JumpBack(pos) => self.jump_back(pos)?,
+        };
+
+        Ok(self.pc)
     }
 
-    fn increment(&mut self) -> ExecutionResult {
-        if self.array[self.pointer] == Int::MAX {
-            Err(ExecutionError::Overflow)
-        } else {
-            self.array[self.pointer] += 1;
-            Ok(ExecutionState::Running)
-        }
+    /// The error the program failed with while it was at the command `pc`, where it is now.
+    pub(crate) fn error_at(&self, kind: ExecutionErrorKind, pc: usize) -> ExecutionError {
+        let context = ErrorContext {
+            pc,
+            position: None,
+            pointer: self.pointer,
+            cell: self.current_cell(),
+        };
+
+        ExecutionError::new(kind, Some(context))
     }
 
-    fn decrement(&mut self) -> ExecutionResult {
-        if self.array[self.pointer] == Int::MIN {
-            Err(ExecutionError::Underflow)
-        } else {
-            self.array[self.pointer] -= 1;
-            Ok(ExecutionState::Running)
+    /// Counts steps of the program, failing if that goes past the step limit or the timeout.
+    pub(crate) fn count_steps(&mut self, steps: u64) -> Result<(), ExecutionErrorKind> {
+        if steps == 0 {
+            return Ok(());
         }
+
+        if !self.has_steps_left(steps) {
+            return Err(ExecutionErrorKind::StepLimitExceeded { steps: self.steps });
+        }
+
+        if let Some(timeout) = self.config.timeout {
+            let started = *self.started.get_or_insert_with(Instant::now);
+            // Looks at the clock if any of the steps is a multiple of the interval.
+            let last = self.steps.saturating_add(steps - 1);
+            let checks = self.steps.is_multiple_of(CLOCK_INTERVAL)
+                || last / CLOCK_INTERVAL > self.steps / CLOCK_INTERVAL;
+            if checks && started.elapsed() >= timeout {
+                return Err(ExecutionErrorKind::Timeout { steps: self.steps });
+            }
+        }
+
+        self.steps = self.steps.saturating_add(steps);
+
+        Ok(())
+    }
+
+    /// Whether the step limit leaves room for this many more steps.
+    pub(crate) fn has_steps_left(&self, steps: u64) -> bool {
+        self.config
+            .max_steps
+            .is_none_or(|max_steps| self.steps.saturating_add(steps) <= max_steps)
     }
 
-    fn output(&mut self) -> ExecutionResult {
-        let data = self.array[self.pointer];
+    pub(crate) fn set_steps(&mut self, steps: u64) {
+        self.steps = steps;
+    }
+
+    pub(crate) fn flush_output(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.output
+            .flush()
+            .map_err(|_err| ExecutionErrorKind::OutputError)
+    }
 
-        if let Some(converted_char) = char::from_u32(data as u32) {
-            print!("{}", converted_char);
+    fn check_state_valid(&self) -> Result<(), ExecutionErrorKind> {
+        if self.pc >= self.commands.len() {
+            Err(ExecutionErrorKind::ProgramCounterOutOfRange)
+        } else if self.pointer < self.tape.lowest_index() {
+            Err(ExecutionErrorKind::TooFarLeft)
+        } else if self.pointer > self.tape.highest_index() {
+            Err(ExecutionErrorKind::TooFarRight)
         } else {
-            return Err(ExecutionError::InvalidCharacter);
+            Ok(())
         }
+    }
+
+    fn move_to_the_right(&mut self, observer: &mut dyn Observer) -> CommandResult {
+        let from = self.pointer;
+        self.pointer = self.tape.move_right(from)?;
+        observer.on_pointer_move(from, self.pointer);
 
         Ok(ExecutionState::Running)
     }
 
-    fn input(&mut self) -> ExecutionResult {
-        let input_char = BufReader::new(io::stdin())
-            .bytes()
-            .next()
-            .ok_or(ExecutionError::InputError)?
-            .map_err(|_err| ExecutionError::InputError)?;
+    fn move_to_the_left(&mut self, observer: &mut dyn Observer) -> CommandResult {
+        let from = self.pointer;
+        self.pointer = self.tape.move_left(from)?;
+        observer.on_pointer_move(from, self.pointer);
 
-        self.array[self.pointer] = input_char as Int;
+        Ok(ExecutionState::Running)
+    }
+
+    pub(crate) fn add_to_cell(&mut self, delta: Int, observer: &mut dyn Observer) -> CommandResult {
+        let cell_type = self.config.cell_type;
+        let cell = self.current_cell();
+
+        let value = match self.config.overflow_policy {
+            OverflowPolicy::Error => match cell.checked_add(delta) {
+                Some(value) if cell_type.contains(value) => value,
+                _ if delta > 0 => return Err(ExecutionErrorKind::Overflow),
+                _ => return Err(ExecutionErrorKind::Underflow),
+            },
+            OverflowPolicy::Wrap => cell_type.wrapping_add(cell, delta),
+            OverflowPolicy::Saturate => cell_type.saturating_add(cell, delta),
+        };
+        self.set_current_cell(value, observer);
 
         Ok(ExecutionState::Running)
     }
 
-    fn jump_forward(&mut self, pos: usize) -> ExecutionResult {
+    pub(crate) fn output(&mut self, observer: &mut dyn Observer) -> CommandResult {
+        let data = self.current_cell();
+        let mut buffer = [0; 4];
+
+        let bytes = self
+            .config
+            .io_mode
+            .encode(data, &mut buffer)
+            .ok_or(ExecutionErrorKind::InvalidCharacter)?;
+        self.output
+            .write_all(bytes)
+            .map_err(|_err| ExecutionErrorKind::OutputError)?;
+        observer.on_output(data);
+
+        Ok(ExecutionState::Running)
+    }
+
+    pub(crate) fn input(&mut self, observer: &mut dyn Observer) -> CommandResult {
+        self.flush_output()?;
+
+        let input_char = self.read_char()?;
+        observer.on_input(input_char);
+        let value = match input_char {
+            Some(input_char) => self.fit_into_cell(input_char)?,
+            None => match self.config.eof_policy {
+                EofPolicy::Zero => 0,
+                EofPolicy::MinusOne => self.config.cell_type.wrap(-1),
+                EofPolicy::Unchanged => return Ok(ExecutionState::Running),
+                EofPolicy::Error => return Err(ExecutionErrorKind::InputError),
+            },
+        };
+
+        self.set_current_cell(value, observer);
+
+        Ok(ExecutionState::Running)
+    }
+
+    /// Reads the next character in the current I/O mode, or `None` if the input has run out.
+    fn read_char(&mut self) -> Result<Option<Int>, ExecutionErrorKind> {
+        let io_mode = self.config.io_mode;
+
+        let Some(first) = self.read_byte()? else {
+            return Ok(None);
+        };
+        let length = io_mode
+            .sequence_length(first)
+            .ok_or(ExecutionErrorKind::InvalidInput)?;
+
+        let mut bytes = [first, 0, 0, 0];
+        for byte in bytes.iter_mut().take(length).skip(1) {
+            *byte = self.read_byte()?.ok_or(ExecutionErrorKind::InvalidInput)?;
+        }
+
+        io_mode
+            .decode(&bytes[..length])
+            .map(Some)
+            .ok_or(ExecutionErrorKind::InvalidInput)
+    }
+
+    /// Makes an input value fit into a cell according to the overflow policy.
+    fn fit_into_cell(&self, value: Int) -> Result<Int, ExecutionErrorKind> {
+        let cell_type = self.config.cell_type;
+
+        if cell_type.contains(value) {
+            return Ok(value);
+        }
+
+        match self.config.overflow_policy {
+            OverflowPolicy::Error => Err(ExecutionErrorKind::Overflow),
+            OverflowPolicy::Wrap => Ok(cell_type.wrap(value)),
+            OverflowPolicy::Saturate => Ok(cell_type.saturate(value)),
+        }
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>, ExecutionErrorKind> {
+        let mut byte = [0];
+
+        loop {
+            match self.input.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => {
+                    self.bytes_read += 1;
+                    return Ok(Some(byte[0]));
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return Err(ExecutionErrorKind::InputError),
+            }
+        }
+    }
+
+    fn jump_forward(&mut self, pos: usize) -> CommandResult {
         if pos > self.commands.len() {
-            Err(ExecutionError::TooFarRight)
+            Err(ExecutionErrorKind::TooFarRight)
         } else {
-            if self.array[self.pointer] == 0 {
+            if self.current_cell() == 0 {
                 self.pc = pos;
             }
             Ok(ExecutionState::Running)
         }
     }
 
-    fn jump_back(&mut self, pos: usize) -> ExecutionResult {
-        if self.array[self.pointer] != 0 {
+    fn jump_back(&mut self, pos: usize) -> CommandResult {
+        if self.current_cell() != 0 {
             self.pc = pos;
         }
 
         Ok(ExecutionState::Running)
     }
+
+    pub(crate) fn current_cell(&self) -> Int {
+        self.tape.get(self.pointer).unwrap_or_default()
+    }
+
+    pub(crate) fn set_current_cell(&mut self, value: Int, observer: &mut dyn Observer) {
+        let old = self.current_cell();
+        self.tape.set(self.pointer, value);
+        observer.on_cell_write(self.pointer, old, value);
+    }
+
+    /// Moves the data pointer one cell at a time, so the tape grows or wraps around exactly like
+    /// it does for a sequence of `>` or `<` commands.
+    pub(crate) fn move_pointer(
+        &mut self,
+        offset: isize,
+        observer: &mut dyn Observer,
+    ) -> CommandResult {
+        for _ in 0..offset.unsigned_abs() {
+            if offset > 0 {
+                self.move_to_the_right(observer)?;
+            } else {
+                self.move_to_the_left(observer)?;
+            }
+        }
+
+        Ok(ExecutionState::Running)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::{
+            cell_type::CellType, eof_policy::EofPolicy, executing_state::ExecutionState,
+            execution_error_kind::ExecutionErrorKind, executor_command::ExecutorCommand,
+            executor_config::ExecutorConfig, io_mode::IoMode, observer::Observer,
+            observer_action::ObserverAction, overflow_policy::OverflowPolicy, tape::TapeMode,
+        },
+        parsing_src::translate_into_commands,
+    };
+
+    use super::{ExecutorState, Int};
+
+    /// Writes down everything it is told about and pauses the program before the command at
+    /// `pause_at`.
+    #[derive(Default)]
+    struct Recorder {
+        pause_at: Option<usize>,
+        events: Vec<String>,
+    }
+
+    impl Observer for Recorder {
+        fn on_step(&mut self, pc: usize, command: &ExecutorCommand) -> ObserverAction {
+            self.events.push(format!("step {pc} {command}"));
+            if self.pause_at == Some(pc) {
+                ObserverAction::Pause
+            } else {
+                ObserverAction::Continue
+            }
+        }
+
+        fn on_cell_write(&mut self, index: isize, old: Int, new: Int) {
+            self.events.push(format!("cell {index} {old} {new}"));
+        }
+
+        fn on_pointer_move(&mut self, from: isize, to: isize) {
+            self.events.push(format!("move {from} {to}"));
+        }
+
+        fn on_input(&mut self, value: Option<Int>) {
+            self.events.push(format!("input {value:?}"));
+        }
+
+        fn on_output(&mut self, value: Int) {
+            self.events.push(format!("output {value}"));
+        }
+    }
+
+    const CELL_TYPES: [CellType; 6] = [
+        CellType::U8,
+        CellType::U16,
+        CellType::U32,
+        CellType::I32,
+        CellType::I64,
+        CellType::I128,
+    ];
+
+    fn start<'a>(
+        source: &str,
+        config: ExecutorConfig,
+        input: &'a [u8],
+    ) -> ExecutorState<&'a [u8], Vec<u8>> {
+        let (commands, _) = translate_into_commands(source, false).unwrap();
+        ExecutorState::with_io(commands, config, input, Vec::new())
+    }
+
+    fn finish(state: &mut ExecutorState<&[u8], Vec<u8>>) -> Result<(), ExecutionErrorKind> {
+        loop {
+            match state.execute_once() {
+                Ok(ExecutionState::Running) => {}
+                Ok(ExecutionState::Finished) => return Ok(()),
+                Err(err) => return Err(err.get_kind()),
+            }
+        }
+    }
+
+    /// Runs a single `+` or `-` on a cell holding `value`, giving back what the cell holds then.
+    fn add_to(
+        value: Int,
+        command: &str,
+        cell_type: CellType,
+        overflow_policy: OverflowPolicy,
+    ) -> Result<Int, ExecutionErrorKind> {
+        let config = ExecutorConfig {
+            cell_type,
+            overflow_policy,
+            ..ExecutorConfig::default()
+        };
+        let mut state = start(command, config, b"");
+        assert!(state.set_cell_value(0, value));
+
+        finish(&mut state)?;
+        Ok(state.get_cell(0).unwrap())
+    }
+
+    #[test]
+    fn overflow_policies_apply_at_the_bounds_of_every_cell_type() {
+        for cell_type in CELL_TYPES {
+            let (min, max) = (cell_type.min_value(), cell_type.max_value());
+
+            assert_eq!(
+                add_to(max, "+", cell_type, OverflowPolicy::Error),
+                Err(ExecutionErrorKind::Overflow)
+            );
+            assert_eq!(
+                add_to(min, "-", cell_type, OverflowPolicy::Error),
+                Err(ExecutionErrorKind::Underflow)
+            );
+            assert_eq!(add_to(max, "+", cell_type, OverflowPolicy::Wrap), Ok(min));
+            assert_eq!(add_to(min, "-", cell_type, OverflowPolicy::Wrap), Ok(max));
+            assert_eq!(
+                add_to(max, "+", cell_type, OverflowPolicy::Saturate),
+                Ok(max)
+            );
+            assert_eq!(
+                add_to(min, "-", cell_type, OverflowPolicy::Saturate),
+                Ok(min)
+            );
+
+            for overflow_policy in [
+                OverflowPolicy::Error,
+                OverflowPolicy::Wrap,
+                OverflowPolicy::Saturate,
+            ] {
+                assert_eq!(add_to(max, "-", cell_type, overflow_policy), Ok(max - 1));
+                assert_eq!(add_to(min, "+", cell_type, overflow_policy), Ok(min + 1));
+            }
+        }
+    }
+
+    #[test]
+    fn failed_additions_leave_the_cell_alone() {
+        let config = ExecutorConfig {
+            cell_type: CellType::U8,
+            overflow_policy: OverflowPolicy::Error,
+            ..ExecutorConfig::default()
+        };
+        let mut state = start("-", config, b"");
+
+        assert_eq!(finish(&mut state), Err(ExecutionErrorKind::Underflow));
+        assert_eq!(state.get_cell(0), Some(0));
+    }
+
+    #[test]
+    fn input_too_large_for_a_cell_follows_the_overflow_policy() {
+        let read = |overflow_policy| {
+            let config = ExecutorConfig {
+                cell_type: CellType::U8,
+                overflow_policy,
+                io_mode: IoMode::Unicode,
+                ..ExecutorConfig::default()
+            };
+            let mut state = start(",", config, "✓".as_bytes());
+            finish(&mut state).map(|()| state.get_cell(0).unwrap())
+        };
+
+        assert_eq!(
+            read(OverflowPolicy::Error),
+            Err(ExecutionErrorKind::Overflow)
+        );
+        assert_eq!(read(OverflowPolicy::Wrap), Ok(0x13));
+        assert_eq!(read(OverflowPolicy::Saturate), Ok(0xff));
+    }
+
+    #[test]
+    fn every_eof_policy_is_applied_once_the_input_runs_out() {
+        let read_past_the_end = |eof_policy, cell_type| {
+            let config = ExecutorConfig {
+                cell_type,
+                eof_policy,
+                ..ExecutorConfig::default()
+            };
+            let mut state = start(",,>+++<,", config, b"a");
+            finish(&mut state).map(|()| state.get_cell(0).unwrap())
+        };
+
+        for cell_type in [CellType::U8, CellType::I32] {
+            assert_eq!(read_past_the_end(EofPolicy::Zero, cell_type), Ok(0));
+            assert_eq!(
+                read_past_the_end(EofPolicy::MinusOne, cell_type),
+                Ok(cell_type.wrap(-1))
+            );
+            assert_eq!(read_past_the_end(EofPolicy::Unchanged, cell_type), Ok(97));
+            assert_eq!(
+                read_past_the_end(EofPolicy::Error, cell_type),
+                Err(ExecutionErrorKind::InputError)
+            );
+        }
+    }
+
+    #[test]
+    fn programs_on_bidirectional_tapes_use_negative_cells() {
+        let config = ExecutorConfig {
+            tape_mode: TapeMode::Bidirectional,
+            ..ExecutorConfig::default()
+        };
+        let mut state = start("+<<++>-", config, b"");
+
+        assert_eq!(finish(&mut state), Ok(()));
+        assert_eq!(state.get_pointer(), -1);
+        assert_eq!(
+            [-2, -1, 0].map(|index| state.get_cell(index)),
+            [Some(2), Some(-1), Some(1)]
+        );
+        assert!(state.set_pointer(-2));
+        assert!(!state.set_pointer(-3));
+    }
+
+    fn echo(io_mode: IoMode, input: &[u8]) -> (Vec<u8>, Result<(), ExecutionErrorKind>) {
+        let config = ExecutorConfig {
+            eof_policy: EofPolicy::Zero,
+            io_mode,
+            ..ExecutorConfig::default()
+        };
+        let mut state = start(",[.,]", config, input);
+        let result = finish(&mut state);
+        (state.into_io().1, result)
+    }
+
+    #[test]
+    fn unicode_input_is_read_and_written_a_character_at_a_time() {
+        let text = "aé✓🦀";
+        let config = ExecutorConfig {
+            io_mode: IoMode::Unicode,
+            ..ExecutorConfig::default()
+        };
+        let mut state = start(",>,>,>,", config, text.as_bytes());
+
+        assert_eq!(finish(&mut state), Ok(()));
+        assert_eq!(
+            [0, 1, 2, 3].map(|index| state.get_cell(index).unwrap()),
+            ['a', 'é', '✓', '🦀'].map(|c| c as Int)
+        );
+        assert_eq!(state.get_bytes_read(), text.len() as u64);
+        assert_eq!(
+            echo(IoMode::Unicode, text.as_bytes()),
+            (text.into(), Ok(()))
+        );
+    }
+
+    #[test]
+    fn byte_input_is_read_and_written_a_byte_at_a_time() {
+        let bytes = [b'a', 0xc3, 0xa9, 0xff, 0x80];
+        let config = ExecutorConfig {
+            io_mode: IoMode::Byte,
+            ..ExecutorConfig::default()
+        };
+        let mut state = start(",>,>,>,>,", config, &bytes);
+
+        assert_eq!(finish(&mut state), Ok(()));
+        assert_eq!(
+            [0, 1, 2, 3, 4].map(|index| state.get_cell(index).unwrap()),
+            bytes.map(Int::from)
+        );
+        assert_eq!(echo(IoMode::Byte, &bytes), (bytes.to_vec(), Ok(())));
+    }
+
+    #[test]
+    fn invalid_utf8_input_is_an_error() {
+        let invalid: [&[u8]; 6] = [
+            &[0xff],
+            &[0x80],
+            &[0xc3],
+            &[0xe2, 0x9c],
+            &[0xc0, 0xaf],
+            &[0xed, 0xa0, 0x80],
+        ];
+
+        for bytes in invalid {
+            let mut input = b"ok".to_vec();
+            input.extend_from_slice(bytes);
+
+            assert_eq!(
+                echo(IoMode::Unicode, &input),
+                (b"ok".to_vec(), Err(ExecutionErrorKind::InvalidInput)),
+                "{bytes:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn only_unicode_output_has_to_be_a_character() {
+        let output = |io_mode, value| {
+            let config = ExecutorConfig {
+                io_mode,
+                ..ExecutorConfig::default()
+            };
+            let mut state = start(".", config, b"");
+            assert!(state.set_cell_value(0, value));
+            finish(&mut state).map(|()| state.into_io().1)
+        };
+
+        for value in [-1, 0xd800, 0x110000] {
+            assert_eq!(
+                output(IoMode::Unicode, value),
+                Err(ExecutionErrorKind::InvalidCharacter)
+            );
+        }
+        assert_eq!(output(IoMode::Unicode, 0xe9), Ok("é".into()));
+        assert_eq!(output(IoMode::Byte, 0xe9), Ok(vec![0xe9]));
+        assert_eq!(output(IoMode::Byte, 0x1e9), Ok(vec![0xe9]));
+        assert_eq!(output(IoMode::Byte, -1), Ok(vec![0xff]));
+    }
+
+    #[test]
+    fn observers_are_told_about_everything_in_order() {
+        let mut state = start(",+>.<,", ExecutorConfig::default(), b"a");
+        let mut recorder = Recorder::default();
+
+        assert!(matches!(
+            state.run_observed(&mut recorder),
+            Err(err) if err.get_kind() == ExecutionErrorKind::InputError
+        ));
+        assert_eq!(
+            recorder.events,
+            [
+                "step 0 Input",
+                "input Some(97)",
+                "cell 0 0 97",
+                "step 1 Increment",
+                "cell 0 97 98",
+                "step 2 MoveRight",
+                "move 0 1",
+                "step 3 Output",
+                "output 0",
+                "step 4 MoveLeft",
+                "move 1 0",
+                "step 5 Input",
+                "input None",
+            ]
+        );
+    }
+
+    #[test]
+    fn pausing_stops_before_the_step() {
+        let mut state = start("+++.", ExecutorConfig::default(), b"");
+        let mut recorder = Recorder {
+            pause_at: Some(2),
+            ..Recorder::default()
+        };
+
+        assert!(matches!(
+            state.run_observed(&mut recorder),
+            Ok(ExecutionState::Running)
+        ));
+        assert_eq!(state.get_pc(), 2);
+        assert_eq!(state.get_steps(), 2);
+        assert_eq!(state.get_cell(0), Some(2));
+        assert_eq!(recorder.events.last().unwrap(), "step 2 Increment");
+
+        assert!(matches!(
+            state.execute_once_observed(&mut recorder),
+            Ok(ExecutionState::Running)
+        ));
+        assert_eq!((state.get_pc(), state.get_steps()), (2, 2));
+
+        recorder.pause_at = None;
+        assert!(matches!(
+            state.run_observed(&mut recorder),
+            Ok(ExecutionState::Finished)
+        ));
+        assert_eq!(state.get_cell(0), Some(3));
+        assert_eq!(state.into_io().1, [3]);
+    }
 }
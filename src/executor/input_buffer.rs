@@ -0,0 +1,99 @@
+use std::{collections::VecDeque, io::Read};
+
+use super::io_mode::IoMode;
+
+/// The input of an `EventExecutor`, which is handed over by the host a piece at a time.
+#[derive(Default)]
+pub struct InputBuffer {
+    bytes: VecDeque<u8>,
+    closed: bool,
+}
+
+impl InputBuffer {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.bytes.extend(bytes);
+    }
+
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
+    /// Whether reading the next character gives a definite answer instead of coming up short
+    /// because the rest of it has not been handed over yet.
+    pub fn is_ready(&self, io_mode: IoMode) -> bool {
+        if self.closed {
+            return true;
+        }
+
+        match self.bytes.front() {
+            Some(&first) => io_mode
+                .sequence_length(first)
+                .is_none_or(|length| self.bytes.len() >= length),
+            None => false,
+        }
+    }
+}
+
+impl Read for InputBuffer {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        self.bytes.read(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crate::executor::io_mode::IoMode;
+
+    use super::InputBuffer;
+
+    #[test]
+    fn empty_buffers_are_ready_once_closed() {
+        let mut buffer = InputBuffer::default();
+        for io_mode in [IoMode::Byte, IoMode::Unicode] {
+            assert!(!buffer.is_ready(io_mode));
+        }
+
+        buffer.close();
+        for io_mode in [IoMode::Byte, IoMode::Unicode] {
+            assert!(buffer.is_ready(io_mode));
+        }
+    }
+
+    #[test]
+    fn characters_are_ready_once_all_their_bytes_are_there() {
+        let mut buffer = InputBuffer::default();
+        let bytes = "€".as_bytes();
+
+        for (handed_over, byte) in bytes.iter().enumerate() {
+            buffer.push(&[*byte]);
+            assert!(buffer.is_ready(IoMode::Byte));
+            assert_eq!(
+                buffer.is_ready(IoMode::Unicode),
+                handed_over + 1 == bytes.len()
+            );
+        }
+    }
+
+    #[test]
+    fn bytes_that_can_not_start_a_character_are_ready() {
+        let mut buffer = InputBuffer::default();
+        buffer.push(&[0xff]);
+
+        // Reading it fails right away, there is nothing to wait for.
+        assert!(buffer.is_ready(IoMode::Unicode));
+    }
+
+    #[test]
+    fn bytes_are_read_in_the_order_they_were_pushed() {
+        let mut buffer = InputBuffer::default();
+        buffer.push(b"ab");
+        buffer.push(b"c");
+
+        let mut read = Vec::new();
+        buffer.read_to_end(&mut read).unwrap();
+        assert_eq!(read, b"abc");
+        assert!(!buffer.is_ready(IoMode::Byte));
+    }
+}
@@ -0,0 +1,64 @@
+use clap::ValueEnum;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use super::executor_state::Int;
+
+/// How the `.` and `,` commands turn cells into bytes and back.
+#[derive(
+    Clone, Copy, Debug, Default, Display, PartialEq, Eq, ValueEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum IoMode {
+    /// Reads single bytes and writes the low 8 bits of the cell.
+    #[display("byte")]
+    Byte,
+    /// Reads whole UTF-8 encoded code points and writes the cell as a UTF-8 encoded code point.
+    #[default]
+    #[display("unicode")]
+    Unicode,
+}
+
+impl IoMode {
+    /// Encodes the value of a cell into `buffer`, giving back the bytes to write, or `None` if the
+    /// value is not a valid Unicode character.
+    pub fn encode<'a>(&self, value: Int, buffer: &'a mut [u8; 4]) -> Option<&'a [u8]> {
+        match self {
+            IoMode::Byte => {
+                buffer[0] = value as u8;
+                Some(&buffer[..1])
+            }
+            IoMode::Unicode => {
+                let converted_char = u32::try_from(value).ok().and_then(char::from_u32)?;
+                Some(converted_char.encode_utf8(buffer).as_bytes())
+            }
+        }
+    }
+
+    /// The total number of bytes of the character whose first byte is `first`, or `None` if
+    /// `first` can not start a character.
+    pub fn sequence_length(&self, first: u8) -> Option<usize> {
+        match self {
+            IoMode::Byte => Some(1),
+            IoMode::Unicode => match first {
+                0x00..=0x7f => Some(1),
+                0xc2..=0xdf => Some(2),
+                0xe0..=0xef => Some(3),
+                0xf0..=0xf4 => Some(4),
+                _ => None,
+            },
+        }
+    }
+
+    /// Decodes the bytes of a single character into the value to store in a cell.
+    pub fn decode(&self, bytes: &[u8]) -> Option<Int> {
+        match self {
+            IoMode::Byte => bytes.first().map(|byte| *byte as Int),
+            IoMode::Unicode => {
+                let mut chars = std::str::from_utf8(bytes).ok()?.chars();
+                let decoded = chars.next()?;
+                chars.next().is_none().then_some(decoded as Int)
+            }
+        }
+    }
+}
@@ -1,4 +1,24 @@
+#[cfg(feature = "async")]
+pub mod async_executor;
+pub mod cell_type;
+pub mod eof_policy;
+pub mod error_context;
+pub mod event_executor;
 pub mod executing_error;
 pub mod executing_state;
+pub mod execution_error_kind;
+pub mod execution_event;
 pub mod executor_command;
+pub mod executor_config;
 pub mod executor_state;
+pub mod input_buffer;
+pub mod io_mode;
+pub mod observer;
+pub mod observer_action;
+pub mod optimized_command;
+pub mod optimized_executor_state;
+pub mod overflow_policy;
+pub mod snapshot;
+pub mod snapshot_error;
+pub mod source_span;
+pub mod tape;
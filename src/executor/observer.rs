@@ -0,0 +1,27 @@
+use super::{
+    executor_command::ExecutorCommand, executor_state::Int, observer_action::ObserverAction,
+};
+
+/// Gets told about everything a program run by `ExecutorState::execute_once_observed` or
+/// `ExecutorState::run_observed` does. Every method does nothing unless it is overridden.
+pub trait Observer {
+    /// Called before the command at `pc` runs, which can pause the program instead.
+    fn on_step(&mut self, _pc: usize, _command: &ExecutorCommand) -> ObserverAction {
+        ObserverAction::Continue
+    }
+
+    /// Called after the value of the cell at `index` has changed from `old` to `new`.
+    fn on_cell_write(&mut self, _index: isize, _old: Int, _new: Int) {}
+
+    /// Called after the data pointer has moved from `from` to `to`.
+    fn on_pointer_move(&mut self, _from: isize, _to: isize) {}
+
+    /// Called after a `,` command has read the value, or found that the input had run out.
+    fn on_input(&mut self, _value: Option<Int>) {}
+
+    /// Called after a `.` command has written the value.
+    fn on_output(&mut self, _value: Int) {}
+}
+
+/// Observes nothing, for programs nobody is watching.
+impl Observer for () {}
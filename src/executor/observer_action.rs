@@ -0,0 +1,9 @@
+/// What a program does after an `Observer` has been told about its next step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ObserverAction {
+    /// Runs the command.
+    #[default]
+    Continue,
+    /// Stops before the command, which runs once the program is resumed.
+    Pause,
+}
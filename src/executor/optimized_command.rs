@@ -0,0 +1,519 @@
+use derive_more::Display;
+
+use super::{
+    executor_command::ExecutorCommand, executor_config::ExecutorConfig, executor_state::Int,
+    overflow_policy::OverflowPolicy, source_span::SourceSpan, tape::TapeMode,
+};
+
+/// A command of the optimized program. Runs of the same command are folded into one command and
+/// common loops are replaced by a single command doing the same thing.
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+pub enum OptimizedCommand {
+    /// Adds the value to the current cell.
+    #[display("Add({})", _0)]
+    Add(Int),
+    /// Moves the data pointer by the offset.
+    #[display("Move({})", _0)]
+    Move(isize),
+    /// Sets the current cell to 0, which is what `[-]` and `[+]` do.
+    Clear,
+    /// Moves the data pointer by the offset until the current cell is 0, which is what loops like
+    /// `[>]` and `[<<]` do.
+    #[display("Scan({})", _0)]
+    Scan(isize),
+    /// Adds the current cell multiplied by the factor to the cell at the offset for every pair,
+    /// then clears the current cell, which is what loops like `[->+>++<<]` do. Does nothing if
+    /// the current cell is 0.
+    #[display("Multiply({:?})", _0)]
+    Multiply(Vec<(isize, Int)>),
+    Output,
+    Input,
+    #[display("JumpForward({})", _0)]
+    JumpForward(usize),
+    #[display("JumpBack({})", _0)]
+    JumpBack(usize),
+}
+
+/// Translates a program into its optimized form.
+///
+/// Loops are only replaced when that can not be told apart from running them, so `Clear` and
+/// `Multiply` are only used if cells wrap around, and runs of `+` and `-` or `>` and `<` are only
+/// cancelled out against each other if cells wrap around.
+pub fn optimize(commands: &[ExecutorCommand], config: &ExecutorConfig) -> Vec<OptimizedCommand> {
+    optimize_with_spans(commands, config).0
+}
+
+/// Translates a program into its optimized form like `optimize`, also giving back the commands as
+/// written that every optimized command stands for. The spans cover the whole program one after
+/// the other, with one more span at the end holding the commands cancelling each other out at
+/// the very end.
+pub fn optimize_with_spans(
+    commands: &[ExecutorCommand],
+    config: &ExecutorConfig,
+) -> (Vec<OptimizedCommand>, Vec<SourceSpan>) {
+    let wrapping = config.overflow_policy == OverflowPolicy::Wrap;
+    let circular_length =
+        (config.tape_mode == TapeMode::Circular).then(|| config.tape_length.max(1) as isize);
+
+    let mut optimized = Optimized::default();
+    let mut loop_starts = Vec::new();
+
+    let mut index = 0;
+    while index < commands.len() {
+        match &commands[index] {
+            ExecutorCommand::Increment => optimized.push_add(1, wrapping, index),
+            ExecutorCommand::Decrement => optimized.push_add(-1, wrapping, index),
+            ExecutorCommand::MoveRight => optimized.push_move(1, index),
+            ExecutorCommand::MoveLeft => optimized.push_move(-1, index),
+            ExecutorCommand::Output => optimized.push(OptimizedCommand::Output, index, index + 1),
+            ExecutorCommand::Input => optimized.push(OptimizedCommand::Input, index, index + 1),
+            ExecutorCommand::JumpForward(end) => {
+                if let Some(command) =
+                    optimize_loop(&commands[index + 1..*end], wrapping, circular_length)
+                {
+                    optimized.push(command, index, *end + 1);
+                    index = *end + 1;
+                    continue;
+                }
+
+                loop_starts.push(optimized.commands.len());
+                optimized.push(OptimizedCommand::JumpForward(0), index, index + 1);
+            }
+            ExecutorCommand::JumpBack(_) => {
+                if let Some(start) = loop_starts.pop() {
+                    optimized.commands[start] =
+                        OptimizedCommand::JumpForward(optimized.commands.len());
+                    optimized.push(OptimizedCommand::JumpBack(start), index, index + 1);
+                }
+            }
+        }
+        index += 1;
+    }
+
+    let end = SourceSpan {
+        start: optimized.covered,
+        origin: commands.len(),
+        end: commands.len(),
+    };
+    optimized.spans.push(end);
+
+    (optimized.commands, optimized.spans)
+}
+
+/// The optimized commands so far, together with the commands as written each one stands for.
+#[derive(Default)]
+struct Optimized {
+    commands: Vec<OptimizedCommand>,
+    spans: Vec<SourceSpan>,
+    covered: usize, // the index right after the commands the optimized commands stand for
+}
+
+impl Optimized {
+    fn push(&mut self, command: OptimizedCommand, origin: usize, end: usize) {
+        self.commands.push(command);
+        self.spans.push(SourceSpan {
+            start: self.covered,
+            origin,
+            end,
+        });
+        self.covered = end;
+    }
+
+    /// Makes the last optimized command stand for the commands up to `end` as well.
+    fn extend(&mut self, end: usize) {
+        if let Some(span) = self.spans.last_mut() {
+            span.end = end;
+        }
+        self.covered = end;
+    }
+
+    fn push_add(&mut self, delta: Int, wrapping: bool, origin: usize) {
+        if let Some(OptimizedCommand::Add(value)) = self.commands.last_mut()
+            && (wrapping || value.signum() == delta.signum())
+        {
+            *value = value.wrapping_add(delta);
+            if *value == 0 {
+                // The cancelled commands go to the optimized command after them.
+                self.commands.pop();
+                if let Some(span) = self.spans.pop() {
+                    self.covered = span.start;
+                }
+            } else {
+                self.extend(origin + 1);
+            }
+            return;
+        }
+
+        self.push(OptimizedCommand::Add(delta), origin, origin + 1);
+    }
+
+    fn push_move(&mut self, offset: isize, origin: usize) {
+        if let Some(OptimizedCommand::Move(value)) = self.commands.last_mut()
+            && value.signum() == offset.signum()
+        {
+            *value += offset;
+            self.extend(origin + 1);
+            return;
+        }
+
+        self.push(OptimizedCommand::Move(offset), origin, origin + 1);
+    }
+}
+
+/// Replaces the loop with the given body by a single command if it is a clear, scan or multiply
+/// loop. On a circular tape of the given length, offsets that wrap around onto the counter keep
+/// the loop as it is.
+fn optimize_loop(
+    body: &[ExecutorCommand],
+    wrapping: bool,
+    circular_length: Option<isize>,
+) -> Option<OptimizedCommand> {
+    let mut offset: isize = 0;
+    let mut visited = (0, 0);
+    let mut deltas: Vec<(isize, Int)> = Vec::new();
+    let mut moves_only = true;
+
+    for command in body {
+        match command {
+            ExecutorCommand::MoveRight => {
+                offset += 1;
+                visited.1 = visited.1.max(offset);
+            }
+            ExecutorCommand::MoveLeft => {
+                offset -= 1;
+                visited.0 = visited.0.min(offset);
+            }
+            ExecutorCommand::Increment | ExecutorCommand::Decrement => {
+                moves_only = false;
+                let delta = if let ExecutorCommand::Increment = command {
+                    1
+                } else {
+                    -1
+                };
+                match deltas.iter_mut().find(|(cell, _)| *cell == offset) {
+                    Some((_, value)) => *value += delta,
+                    None => deltas.push((offset, delta)),
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    if moves_only {
+        let uniform = body.windows(2).all(|pair| {
+            matches!(
+                pair,
+                [ExecutorCommand::MoveRight, ExecutorCommand::MoveRight]
+                    | [ExecutorCommand::MoveLeft, ExecutorCommand::MoveLeft]
+            )
+        });
+        return (offset != 0 && uniform).then_some(OptimizedCommand::Scan(offset));
+    }
+
+    if !wrapping || offset != 0 {
+        return None;
+    }
+
+    let counter = deltas
+        .iter()
+        .find(|(cell, _)| *cell == 0)
+        .map(|(_, delta)| *delta)?;
+
+    // The loop runs `value` times if the counter goes down by one and `-value` times if it goes
+    // up by one, so the factors are flipped in the latter case.
+    let sign = match counter {
+        -1 => 1,
+        1 => -1,
+        _ => return None,
+    };
+
+    let factors: Vec<(isize, Int)> = deltas
+        .into_iter()
+        .filter(|(cell, delta)| *cell != 0 && *delta != 0)
+        .map(|(cell, delta)| (cell, delta * sign))
+        .collect();
+
+    // Running the loop would move the data pointer to every visited cell, so the loop is only
+    // replaced if those are exactly the cells it changes.
+    let touched = factors.iter().fold((0, 0), |(low, high), (cell, _)| {
+        (low.min(*cell), high.max(*cell))
+    });
+    if touched != visited {
+        return None;
+    }
+
+    if let Some(length) = circular_length
+        && factors.iter().any(|(cell, _)| cell.rem_euclid(length) == 0)
+    {
+        return None;
+    }
+
+    if factors.is_empty() {
+        Some(OptimizedCommand::Clear)
+    } else {
+        Some(OptimizedCommand::Multiply(factors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::{
+        executor::{
+            cell_type::CellType, executing_error::ExecutionError, executing_state::ExecutionState,
+            execution_error_kind::ExecutionErrorKind, executor_config::ExecutorConfig,
+            executor_state::ExecutorState, optimized_executor_state::OptimizedExecutorState,
+            overflow_policy::OverflowPolicy, tape::TapeMode,
+        },
+        parsing_src::{source_positions, translate_into_commands},
+    };
+
+    use super::{OptimizedCommand, optimize};
+
+    /// Programs built around clear, scan and multiply loops, including ones that leave the tape,
+    /// go past the bounds of a cell, wrap around onto their own counter on a circular tape or run
+    /// into the step limit.
+    const PROGRAMS: &[&str] = &[
+        "+++++[-]>--[+]<.>.",
+        "+++---+>><<-[-]",
+        "++>+++<[->+<]>.",
+        "+++[->++>+++<<]>.>.",
+        "+++[>+<-]>.",
+        "-[+>+<]>.",
+        "+[->-<]>.",
+        "++[->>>+<<<]>>>.",
+        "++[->>>>>+<<<<<]>>>>>.",
+        "-[+]",
+        "-[+>+<]>.",
+        "+++[-<+>]",
+        "+[->+<<+>]",
+        "++++++++[->++++++++<]>[->++++<]>.",
+        "+>+>+>+<<<[>]+.",
+        "+>+>+[<]+.",
+        ">>+<+<+[>>]+.",
+        "+[<<]",
+        "+[>>>>>>>>]",
+        "--[+>+>+<<]>.>.",
+        "-[+>+>+<<]>.>.",
+        "++++[->+++[->++<]<]>>.",
+        ">+++++++++++<<",
+        "+>>>>>>>>>+<<<<<<<<<<<",
+        "+[->>+<<]>>.",
+        "+[-<+>]",
+        "+>+[>]",
+        "++++++++[->++++++++<]>[->++++<]>[-<++++>]<[->++<]",
+        "+>+<+>+<[>]",
+    ];
+
+    #[derive(Debug, PartialEq)]
+    struct Outcome {
+        output: Vec<u8>,
+        result: Result<(), ExecutionError>,
+        steps: u64,
+        tape: (Vec<(isize, i128)>, isize),
+    }
+
+    impl Outcome {
+        fn new<R: io::Read>(
+            result: Result<(), ExecutionError>,
+            state: &ExecutorState<R, Vec<u8>>,
+        ) -> Outcome {
+            Outcome {
+                output: state.get_output().clone(),
+                result,
+                steps: state.get_steps(),
+                tape: (state.get_tape().iter().collect(), state.get_pointer()),
+            }
+        }
+    }
+
+    fn run_interpreter(source: &str, config: ExecutorConfig) -> Outcome {
+        let (commands, _) = translate_into_commands(source, false).unwrap();
+        let mut state = ExecutorState::with_io(commands, config, io::empty(), Vec::new());
+        let result = run_to_end(|| state.execute_once());
+
+        Outcome::new(result, &state)
+    }
+
+    fn run_optimized(source: &str, config: ExecutorConfig) -> Outcome {
+        let (commands, _) = translate_into_commands(source, false).unwrap();
+        let mut state = OptimizedExecutorState::with_io(&commands, config, io::empty(), Vec::new());
+        let result = run_to_end(|| state.execute_once());
+
+        Outcome::new(result, state.get_state())
+    }
+
+    fn run_to_end(
+        mut execute_once: impl FnMut() -> Result<ExecutionState, ExecutionError>,
+    ) -> Result<(), ExecutionError> {
+        loop {
+            match execute_once() {
+                Ok(ExecutionState::Running) => {}
+                Ok(ExecutionState::Finished) => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn assert_same_behaviour(tape_mode: TapeMode, tape_length: usize) {
+        for cell_type in [CellType::U8, CellType::I32, CellType::I128] {
+            for overflow_policy in [
+                OverflowPolicy::Wrap,
+                OverflowPolicy::Saturate,
+                OverflowPolicy::Error,
+            ] {
+                let config = ExecutorConfig {
+                    cell_type,
+                    overflow_policy,
+                    tape_mode,
+                    tape_length,
+                    max_steps: Some(20000),
+                    ..ExecutorConfig::default()
+                };
+
+                for program in PROGRAMS {
+                    assert_eq!(
+                        run_optimized(program, config),
+                        run_interpreter(program, config),
+                        "{} with {:?}",
+                        program,
+                        config
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn growable_tape_behaves_like_the_interpreter() {
+        assert_same_behaviour(TapeMode::Growable, 1);
+    }
+
+    #[test]
+    fn bidirectional_tape_behaves_like_the_interpreter() {
+        assert_same_behaviour(TapeMode::Bidirectional, 1);
+    }
+
+    #[test]
+    fn fixed_tape_behaves_like_the_interpreter() {
+        for tape_length in [1, 3, 4, 8] {
+            assert_same_behaviour(TapeMode::Fixed, tape_length);
+        }
+    }
+
+    #[test]
+    fn circular_tape_behaves_like_the_interpreter() {
+        for tape_length in [1, 2, 3, 4, 5, 8] {
+            assert_same_behaviour(TapeMode::Circular, tape_length);
+        }
+    }
+
+    #[test]
+    fn step_limits_stop_where_the_interpreter_stops() {
+        let programs = [
+            "+-+-[-]",
+            "+-++[->++<]>.<+-",
+            "+>+>+<<[>]<-[<]",
+            "++[>++[>+++<-]<-]>>.",
+            "+-><",
+        ];
+
+        for cell_type in [CellType::U8, CellType::I32] {
+            for max_steps in 0..200 {
+                let config = ExecutorConfig {
+                    cell_type,
+                    max_steps: Some(max_steps),
+                    ..ExecutorConfig::default()
+                };
+
+                for program in programs.iter().chain(PROGRAMS) {
+                    assert_eq!(
+                        run_optimized(program, config),
+                        run_interpreter(program, config),
+                        "{} with {:?}",
+                        program,
+                        config
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn max_cells_behave_like_the_interpreter() {
+        for tape_mode in [TapeMode::Growable, TapeMode::Bidirectional] {
+            for max_cells in [1, 2, 3, 5] {
+                let config = ExecutorConfig {
+                    cell_type: CellType::U8,
+                    tape_mode,
+                    max_cells: Some(max_cells),
+                    max_steps: Some(20000),
+                    ..ExecutorConfig::default()
+                };
+
+                for program in PROGRAMS {
+                    assert_eq!(
+                        run_optimized(program, config),
+                        run_interpreter(program, config),
+                        "{} with {:?}",
+                        program,
+                        config
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn failing_moves_report_the_command_that_fails() {
+        let source = ">+++++++++++<<";
+        let config = ExecutorConfig::default();
+        let err = run_optimized(source, config)
+            .result
+            .unwrap_err()
+            .locate(&source_positions(source));
+
+        assert_eq!(err.get_kind(), ExecutionErrorKind::TooFarLeft);
+        let context = err.get_context().unwrap();
+        assert_eq!(context.pc, 13);
+        assert_eq!(context.position.unwrap().column, 14);
+        assert_eq!(context.pointer, 0);
+    }
+
+    #[test]
+    fn loops_are_replaced() {
+        let config = ExecutorConfig::default();
+        let optimized = |source| {
+            let (commands, _) = translate_into_commands(source, false).unwrap();
+            optimize(&commands, &config)
+        };
+
+        assert_eq!(optimized("[-]"), vec![OptimizedCommand::Clear]);
+        assert_eq!(optimized("[<<]"), vec![OptimizedCommand::Scan(-2)]);
+        assert_eq!(
+            optimized("[->+>++<<]"),
+            vec![OptimizedCommand::Multiply(vec![(1, 1), (2, 2)])]
+        );
+        assert_eq!(
+            optimized("++-->>><"),
+            vec![OptimizedCommand::Move(3), OptimizedCommand::Move(-1)]
+        );
+    }
+
+    #[test]
+    fn multiply_loops_wrapping_onto_their_counter_are_kept() {
+        let (commands, _) = translate_into_commands("[->>>+<<<]", false).unwrap();
+        let config = ExecutorConfig {
+            tape_mode: TapeMode::Circular,
+            tape_length: 3,
+            ..ExecutorConfig::default()
+        };
+
+        assert!(
+            !optimize(&commands, &config)
+                .iter()
+                .any(|command| matches!(command, OptimizedCommand::Multiply(_)))
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::io::{self, Read, Stdin, Stdout, Write};
+
+use super::{
+    cell_type::CellType,
+    executing_state::ExecutionState,
+    execution_error_kind::ExecutionErrorKind,
+    executor_command::ExecutorCommand,
+    executor_config::ExecutorConfig,
+    executor_state::{CommandResult, ExecutionResult, ExecutorState, Int},
+    optimized_command::{OptimizedCommand, optimize_with_spans},
+    source_span::SourceSpan,
+};
+
+/// Runs the optimized form of a program. The cells, the tape and the I/O behave exactly like they
+/// do in `ExecutorState`, but the program counter refers to the optimized commands.
+///
+/// Every optimized command counts the steps its commands as written would take. If the step limit
+/// does not leave room for a whole optimized command, or the command fails, its commands as
+/// written are run instead, so the program stops at the same command as `ExecutorState`.
+pub struct OptimizedExecutorState<R = Stdin, W = Stdout> {
+    state: ExecutorState<R, W>, // holds the program as written, to run it one command at a time
+    pc: usize,
+    commands: Vec<OptimizedCommand>,
+    spans: Vec<SourceSpan>,
+    replaying: bool, // whether the current command is run as written
+}
+
+impl OptimizedExecutorState {
+    pub fn new(commands: &[ExecutorCommand], config: ExecutorConfig) -> OptimizedExecutorState {
+        Self::with_io(commands, config, io::stdin(), io::stdout())
+    }
+}
+
+impl<R: Read, W: Write> OptimizedExecutorState<R, W> {
+    pub fn with_io(
+        commands: &[ExecutorCommand],
+        config: ExecutorConfig,
+        input: R,
+        output: W,
+    ) -> OptimizedExecutorState<R, W> {
+        let (optimized, spans) = optimize_with_spans(commands, &config);
+
+        OptimizedExecutorState {
+            state: ExecutorState::with_io(commands.to_vec(), config, input, output),
+            pc: 0,
+            commands: optimized,
+            spans,
+            replaying: false,
+        }
+    }
+
+    pub fn execute_once(&mut self) -> ExecutionResult {
+        if !self.replaying {
+            let result = self
+                .run_command()
+                .map_err(|kind| self.state.error_at(kind, self.spans[self.pc].origin));
+            if !self.replaying {
+                return result;
+            }
+        }
+
+        let result = self.state.execute_once()?;
+        // Goes back to the optimized commands once the current one has been run as written.
+        if self.pc < self.commands.len() && self.state.get_pc() == self.spans[self.pc].end {
+            self.replaying = false;
+            self.pc += 1;
+        }
+
+        Ok(result)
+    }
+
+    fn run_command(&mut self) -> CommandResult {
+        let span = self.spans[self.pc];
+        let (pointer, steps) = (self.state.get_pointer(), self.state.get_steps());
+
+        if self.pc >= self.commands.len() {
+            // The commands cancelling each other out at the very end still take their steps.
+            if !self.count_steps(span.len() as u64) {
+                return Ok(self.replay(span.start));
+            }
+            self.state.flush_output()?;
+            return Ok(ExecutionState::Finished);
+        }
+
+        if let OptimizedCommand::Multiply(factors) = &self.commands[self.pc]
+            && self.state.current_cell() != 0
+        {
+            let (low, high) = factors.iter().fold((0, 0), |(low, high), (offset, _)| {
+                (low.min(*offset), high.max(*offset))
+            });
+            // The loop is run as written if it fails, so it stops at the same command.
+            if !self
+                .state
+                .get_tape()
+                .can_reach(pointer + low, pointer + high)
+            {
+                return Ok(self.replay(span.start));
+            }
+        }
+
+        let cost = match &self.commands[self.pc] {
+            OptimizedCommand::Clear | OptimizedCommand::Multiply(_) => self.loop_steps(span),
+            // Every iteration of a scan is counted on its own.
+            OptimizedCommand::Scan(_) => (span.origin - span.start) as u64 + 1,
+            _ => span.len() as u64,
+        };
+        if !self.count_steps(cost) {
+            return Ok(self.replay(span.start));
+        }
+
+        match &self.commands[self.pc] {
+            OptimizedCommand::Add(delta) => {
+                if self.state.add_to_cell(*delta, &mut ()).is_err() {
+                    return Ok(self.undo_and_replay(pointer, steps, span.start));
+                }
+            }
+            OptimizedCommand::Move(offset) => {
+                if self.state.move_pointer(*offset, &mut ()).is_err() {
+                    return Ok(self.undo_and_replay(pointer, steps, span.start));
+                }
+            }
+            OptimizedCommand::Clear => self.state.set_current_cell(0, &mut ()),
+            OptimizedCommand::Scan(offset) => {
+                let offset = *offset;
+                // The moves of an iteration and the `]` jumping back.
+                let iteration = offset.unsigned_abs() as u64 + 1;
+                while self.state.current_cell() != 0 {
+                    let (pointer, steps) = (self.state.get_pointer(), self.state.get_steps());
+                    if !self.count_steps(iteration)
+                        || self.state.move_pointer(offset, &mut ()).is_err()
+                    {
+                        return Ok(self.undo_and_replay(pointer, steps, span.origin + 1));
+                    }
+                }
+            }
+            OptimizedCommand::Multiply(factors) => {
+                let value = self.state.current_cell();
+                if value != 0 {
+                    // Every cell can be reached, so the moves can not fail.
+                    for (offset, factor) in factors {
+                        self.state.move_pointer(*offset, &mut ())?;
+                        self.state
+                            .add_to_cell(factor.wrapping_mul(value), &mut ())?;
+                        self.state.move_pointer(-offset, &mut ())?;
+                    }
+                    self.state.set_current_cell(0, &mut ());
+                }
+            }
+            OptimizedCommand::Output => {
+                self.state.output(&mut ())?;
+            }
+            OptimizedCommand::Input => {
+                self.state.input(&mut ())?;
+            }
+            OptimizedCommand::JumpForward(pos) => {
+                if *pos >= self.commands.len() {
+                    return Err(ExecutionErrorKind::TooFarRight);
+                }
+                if self.state.current_cell() == 0 {
+                    self.pc = *pos;
+                }
+            }
+            OptimizedCommand::JumpBack(pos) => {
+                if self.state.current_cell() != 0 {
+                    self.pc = *pos;
+                }
+            }
+        }
+        self.pc += 1;
+
+        Ok(ExecutionState::Running)
+    }
+
+    /// Counts the steps if the step limit leaves room for all of them, giving back whether it
+    /// did. Timeouts are left to `ExecutorState`, which checks the clock as it runs.
+    fn count_steps(&mut self, steps: u64) -> bool {
+        self.state.has_steps_left(steps) && self.state.count_steps(steps).is_ok()
+    }
+
+    /// Runs the current command as written from `from` on, with the data as it is now, so it
+    /// stops at exactly the same command as `ExecutorState` would.
+    fn replay(&mut self, from: usize) -> ExecutionState {
+        self.state.set_pc(from);
+        self.replaying = true;
+        ExecutionState::Running
+    }
+
+    /// Puts the data pointer and the steps back to where they were before the current command
+    /// failed, then runs it as written from `from` on.
+    fn undo_and_replay(&mut self, pointer: isize, steps: u64, from: usize) -> ExecutionState {
+        self.state.set_pointer(pointer);
+        self.state.set_steps(steps);
+        self.replay(from)
+    }
+
+    /// The steps a clear or multiply loop takes when the interpreter runs it, which are the `[`
+    /// and every iteration of the loop together with its `]`.
+    fn loop_steps(&self, span: SourceSpan) -> u64 {
+        let before = (span.origin - span.start) as u64 + 1;
+        let value = self.state.current_cell();
+        if value == 0 {
+            return before;
+        }
+
+        let body = &self.state.get_commands()[span.origin + 1..span.end - 1];
+        let iterations =
+            loop_iterations(value, counter_step(body), self.state.get_config().cell_type);
+
+        iterations
+            .saturating_mul(body.len() as u64 + 1)
+            .saturating_add(before)
+    }
+
+    pub fn get_commands(&self) -> &Vec<OptimizedCommand> {
+        &self.commands
+    }
+
+    pub fn get_pc(&self) -> usize {
+        self.pc
+    }
+
+    /// The underlying state holding the tape, the configuration and the I/O.
+    pub fn get_state(&self) -> &ExecutorState<R, W> {
+        &self.state
+    }
+
+    pub fn into_io(self) -> (R, W) {
+        self.state.into_io()
+    }
+}
+
+/// How much the loop body changes the cell it starts at, which is 1 or -1 for clear and multiply
+/// loops.
+fn counter_step(body: &[ExecutorCommand]) -> Int {
+    let mut offset = 0;
+    let mut step = 0;
+
+    for command in body {
+        match command {
+            ExecutorCommand::MoveRight => offset += 1,
+            ExecutorCommand::MoveLeft => offset -= 1,
+            ExecutorCommand::Increment if offset == 0 => step += 1,
+            ExecutorCommand::Decrement if offset == 0 => step -= 1,
+            _ => {}
+        }
+    }
+
+    step
+}
+
+/// The number of times a loop whose counter wraps around and changes by `step` every iteration
+/// runs, starting from `value`. Gives back `u64::MAX` if that does not fit.
+fn loop_iterations(value: Int, step: Int, cell_type: CellType) -> u64 {
+    // The counter reaches 0 after `-value * step` iterations, modulo the width of the cell.
+    let distance = value.wrapping_mul(step).wrapping_neg();
+    let iterations = match cell_type {
+        CellType::I128 => distance as u128,
+        _ => distance.rem_euclid(cell_type.max_value() - cell_type.min_value() + 1) as u128,
+    };
+
+    u64::try_from(iterations).unwrap_or(u64::MAX)
+}
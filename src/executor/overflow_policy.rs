@@ -0,0 +1,21 @@
+use clap::ValueEnum;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+/// What happens when a cell is incremented past its maximum or decremented past its minimum.
+#[derive(
+    Clone, Copy, Debug, Default, Display, PartialEq, Eq, ValueEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Stops the program with an overflow or underflow error.
+    #[display("error")]
+    Error,
+    /// Wraps around modulo the width of the cell.
+    #[default]
+    #[display("wrap")]
+    Wrap,
+    /// Stays at the maximum or minimum of the cell.
+    #[display("saturate")]
+    Saturate,
+}
@@ -0,0 +1,252 @@
+use std::{
+    io::{Read, Write},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::source_position::SourcePosition;
+
+use super::{
+    executor_command::ExecutorCommand, executor_config::ExecutorConfig,
+    snapshot_error::SnapshotError, tape::Tape,
+};
+
+/// Everything needed to resume a running program later, see `ExecutorState::snapshot` and
+/// `ExecutorState::from_snapshot`. Snapshots are saved as JSON.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub(crate) commands: Vec<ExecutorCommand>,
+    pub(crate) config: ExecutorConfig,
+    pub(crate) tape: Tape,
+    pub(crate) pointer: isize,
+    pub(crate) pc: usize,
+    pub(crate) steps: u64,
+    pub(crate) bytes_read: u64,
+    #[serde(default)]
+    pub(crate) positions: Vec<SourcePosition>, // where the commands are in the source, if known
+}
+
+impl Snapshot {
+    /// Reads a snapshot written by `write`, making sure the program can be resumed from it.
+    pub fn read(reader: impl Read) -> Result<Snapshot, SnapshotError> {
+        let snapshot: Snapshot = serde_json::from_reader(reader).map_err(|err| {
+            if err.is_io() {
+                SnapshotError::ReadError
+            } else {
+                SnapshotError::InvalidSnapshot
+            }
+        })?;
+
+        if snapshot.is_valid() {
+            Ok(snapshot)
+        } else {
+            Err(SnapshotError::InvalidSnapshot)
+        }
+    }
+
+    pub fn write(&self, mut writer: impl Write) -> Result<(), SnapshotError> {
+        serde_json::to_writer(&mut writer, self).map_err(|_err| SnapshotError::WriteError)?;
+        writer.flush().map_err(|_err| SnapshotError::WriteError)
+    }
+
+    pub fn get_config(&self) -> &ExecutorConfig {
+        &self.config
+    }
+
+    /// The number of bytes the program had read from its input. The input of the resumed program
+    /// has to start right after them.
+    pub fn get_bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    pub fn get_steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Where every command is in the source code, as found by `parsing_src::source_positions`, or
+    /// nothing if that is not known.
+    pub fn get_positions(&self) -> &[SourcePosition] {
+        &self.positions
+    }
+
+    /// Keeps where every command is in the source code, so errors of the resumed program can be
+    /// located.
+    pub fn set_positions(&mut self, positions: Vec<SourcePosition>) {
+        self.positions = positions;
+    }
+
+    /// Replaces the limits the program was running with. The steps already taken count towards
+    /// the step limit.
+    pub fn set_limits(
+        &mut self,
+        max_steps: Option<u64>,
+        timeout: Option<Duration>,
+        max_cells: Option<usize>,
+    ) {
+        self.config.max_steps = max_steps;
+        self.config.timeout = timeout;
+        self.config.max_cells = max_cells;
+    }
+
+    fn is_valid(&self) -> bool {
+        self.jumps_match()
+            && self.pc <= self.commands.len()
+            && (self.positions.is_empty() || self.positions.len() == self.commands.len())
+            && self.tape.get_mode() == self.config.tape_mode
+            && self.tape.is_consistent(self.config.tape_length)
+            && self.tape.contains(self.pointer)
+            && self
+                .tape
+                .iter()
+                .all(|(_, value)| self.config.cell_type.contains(value))
+    }
+
+    /// Whether every jump leads to its matching jump.
+    fn jumps_match(&self) -> bool {
+        self.commands
+            .iter()
+            .enumerate()
+            .all(|(index, command)| match command {
+                ExecutorCommand::JumpForward(target) => matches!(
+                    self.commands.get(*target),
+                    Some(ExecutorCommand::JumpBack(back)) if *back == index
+                ),
+                ExecutorCommand::JumpBack(target) => matches!(
+                    self.commands.get(*target),
+                    Some(ExecutorCommand::JumpForward(forward)) if *forward == index
+                ),
+                _ => true,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use crate::{
+        executor::{
+            cell_type::CellType, eof_policy::EofPolicy, executing_state::ExecutionState,
+            executor_command::ExecutorCommand, executor_config::ExecutorConfig,
+            executor_state::ExecutorState, snapshot_error::SnapshotError, tape::TapeMode,
+        },
+        parsing_src::{source_positions, translate_into_commands},
+        source_position::SourcePosition,
+    };
+
+    use super::Snapshot;
+
+    const SOURCE: &str = "++++++++[<++++++++>-]<+.>,[<.>.,]";
+    const INPUT: &[u8] = b"snapshot";
+
+    fn config() -> ExecutorConfig {
+        ExecutorConfig {
+            tape_mode: TapeMode::Bidirectional,
+            eof_policy: EofPolicy::Zero,
+            ..ExecutorConfig::default()
+        }
+    }
+
+    fn start(input: &[u8]) -> ExecutorState<&[u8], Vec<u8>> {
+        let (commands, _) = translate_into_commands(SOURCE, false).unwrap();
+        ExecutorState::with_io(commands, config(), input, Vec::new())
+    }
+
+    fn finish<R: Read, W: Write>(state: &mut ExecutorState<R, W>) {
+        while let ExecutionState::Running = state.execute_once().unwrap() {}
+    }
+
+    fn snapshot_after(steps: u64) -> Snapshot {
+        let mut state = start(INPUT);
+        for _ in 0..steps {
+            state.execute_once().unwrap();
+        }
+        state.snapshot()
+    }
+
+    fn read(json: &str) -> Result<Snapshot, SnapshotError> {
+        Snapshot::read(json.as_bytes())
+    }
+
+    #[test]
+    fn resumed_programs_continue_where_they_stopped() {
+        let mut uninterrupted = start(INPUT);
+        finish(&mut uninterrupted);
+        let expected = uninterrupted.get_output().clone();
+        let total = uninterrupted.get_steps();
+
+        for steps in [0, 1, 30, 150, total / 2, total - 1, total] {
+            let mut state = start(INPUT);
+            for _ in 0..steps {
+                state.execute_once().unwrap();
+            }
+            let mut saved = state.snapshot();
+            saved.set_positions(source_positions(SOURCE));
+
+            let mut file = Vec::new();
+            saved.write(&mut file).unwrap();
+            let saved = Snapshot::read(file.as_slice()).unwrap();
+            assert_eq!(saved.get_steps(), steps);
+            assert_eq!(saved.get_positions(), source_positions(SOURCE));
+
+            let rest = &INPUT[saved.get_bytes_read() as usize..];
+            let (_, mut output) = state.into_io();
+            let mut resumed = ExecutorState::from_snapshot(saved, rest, Vec::new());
+            finish(&mut resumed);
+
+            output.extend_from_slice(resumed.get_output());
+            assert_eq!(output, expected, "resumed after {steps} steps");
+            assert_eq!(resumed.get_steps(), total);
+        }
+    }
+
+    #[test]
+    fn malformed_snapshots_are_rejected() {
+        let mut json = Vec::new();
+        snapshot_after(40).write(&mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+
+        for malformed in ["", "{", "[]", "null", &json[..json.len() / 2], &json[1..]] {
+            assert!(
+                matches!(read(malformed), Err(SnapshotError::InvalidSnapshot)),
+                "{malformed:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn inconsistent_snapshots_are_rejected() {
+        let inconsistencies: [fn(&mut Snapshot); 7] = [
+            |saved| saved.pc = saved.commands.len() + 1,
+            |saved| saved.pointer = saved.tape.highest_index() + 1,
+            |saved| saved.commands[8] = ExecutorCommand::JumpForward(0),
+            |saved| saved.commands.truncate(20),
+            |saved| saved.positions = vec![SourcePosition { line: 1, column: 1 }],
+            |saved| saved.config.tape_mode = TapeMode::Growable,
+            |saved| {
+                saved.config.cell_type = CellType::U8;
+                saved.tape.set(0, 300);
+            },
+        ];
+
+        for (index, make_inconsistent) in inconsistencies.into_iter().enumerate() {
+            let mut saved = snapshot_after(120);
+            make_inconsistent(&mut saved);
+
+            let mut json = Vec::new();
+            saved.write(&mut json).unwrap();
+            assert!(
+                matches!(
+                    Snapshot::read(json.as_slice()),
+                    Err(SnapshotError::InvalidSnapshot)
+                ),
+                "inconsistency {index}"
+            );
+        }
+
+        let mut json = Vec::new();
+        snapshot_after(120).write(&mut json).unwrap();
+        assert!(Snapshot::read(json.as_slice()).is_ok());
+    }
+}
@@ -0,0 +1,11 @@
+use derive_more::Display;
+
+#[derive(Debug, Display)]
+pub enum SnapshotError {
+    #[display("Snapshot Error: Unable to read the snapshot!")]
+    ReadError,
+    #[display("Snapshot Error: Unable to write the snapshot!")]
+    WriteError,
+    #[display("Snapshot Error: The file is not a valid snapshot!")]
+    InvalidSnapshot,
+}
@@ -0,0 +1,22 @@
+/// The commands as written that an optimized command stands for, see `optimize_with_spans`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SourceSpan {
+    /// The first command the optimized command stands for. Runs of `+` and `-` cancelling each
+    /// other out belong to the optimized command after them.
+    pub start: usize,
+    /// The command the optimized command starts at.
+    pub origin: usize,
+    /// The index right after the last command the optimized command stands for.
+    pub end: usize,
+}
+
+impl SourceSpan {
+    /// The number of commands the span holds.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
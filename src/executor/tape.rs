@@ -0,0 +1,269 @@
+use std::{collections::VecDeque, fmt};
+
+use clap::ValueEnum;
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use super::{execution_error_kind::ExecutionErrorKind, executor_state::Int};
+
+const INITIAL_SIZE: usize = 32;
+
+/// The shape of the tape the data pointer moves on.
+#[derive(
+    Clone, Copy, Debug, Default, Display, PartialEq, Eq, ValueEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum TapeMode {
+    /// Starts at cell 0 and grows to the right whenever needed.
+    #[default]
+    #[display("growable")]
+    Growable,
+    /// Grows in both directions whenever needed, so cells can have negative indices.
+    #[display("bidirectional")]
+    Bidirectional,
+    /// Has a fixed number of cells, going past either end is an error.
+    #[display("fixed")]
+    Fixed,
+    /// Has a fixed number of cells, going past either end wraps around to the other end.
+    #[display("circular")]
+    Circular,
+}
+
+/// The cells of a brainfuck program. Cells are addressed by their logical index, which is
+/// negative for cells to the left of the starting cell.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Tape {
+    cells: VecDeque<Int>,
+    origin: usize, // the position of cell 0 in `cells`
+    mode: TapeMode,
+    #[serde(skip)]
+    max_cells: Option<usize>,
+}
+
+impl Tape {
+    /// Creates a tape of the given mode. `length` is the number of cells of a fixed or circular
+    /// tape and is ignored otherwise.
+    pub fn new(mode: TapeMode, length: usize) -> Tape {
+        Self::with_max_cells(mode, length, None)
+    }
+
+    /// Creates a tape like `new`, which fails to grow past `max_cells` cells. Fixed and circular
+    /// tapes never grow, `ExecutorConfig::check_tape` checks their length against the limit.
+    pub fn with_max_cells(mode: TapeMode, length: usize, max_cells: Option<usize>) -> Tape {
+        let cells = match mode {
+            TapeMode::Growable | TapeMode::Bidirectional => {
+                let mut cells = VecDeque::with_capacity(INITIAL_SIZE);
+                cells.push_back(0);
+                cells
+            }
+            TapeMode::Fixed | TapeMode::Circular => VecDeque::from(vec![0; length.max(1)]),
+        };
+
+        Tape {
+            cells,
+            origin: 0,
+            mode,
+            max_cells,
+        }
+    }
+
+    pub fn get_mode(&self) -> TapeMode {
+        self.mode
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn lowest_index(&self) -> isize {
+        -(self.origin as isize)
+    }
+
+    pub fn highest_index(&self) -> isize {
+        (self.cells.len() - self.origin) as isize - 1
+    }
+
+    pub fn contains(&self, index: isize) -> bool {
+        (self.lowest_index()..=self.highest_index()).contains(&index)
+    }
+
+    pub fn get(&self, index: isize) -> Option<Int> {
+        self.position(index).map(|position| self.cells[position])
+    }
+
+    pub fn set(&mut self, index: isize, value: Int) -> bool {
+        if let Some(position) = self.position(index) {
+            self.cells[position] = value;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Iterates over all cells from the lowest index to the highest one, together with their
+    /// indices.
+    pub fn iter(&self) -> impl Iterator<Item = (isize, Int)> + '_ {
+        (self.lowest_index()..).zip(self.cells.iter().copied())
+    }
+
+    /// Returns the index to the right of `index`, growing or wrapping around the tape if needed.
+    pub fn move_right(&mut self, index: isize) -> Result<isize, ExecutionErrorKind> {
+        let index = index + 1;
+
+        if index > self.highest_index() {
+            match self.mode {
+                TapeMode::Growable | TapeMode::Bidirectional => {
+                    self.check_room()?;
+                    self.cells.push_back(0);
+                }
+                TapeMode::Fixed => return Err(ExecutionErrorKind::TooFarRight),
+                TapeMode::Circular => return Ok(self.lowest_index()),
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Returns the index to the left of `index`, growing or wrapping around the tape if needed.
+    pub fn move_left(&mut self, index: isize) -> Result<isize, ExecutionErrorKind> {
+        let index = index - 1;
+
+        if index < self.lowest_index() {
+            match self.mode {
+                TapeMode::Growable | TapeMode::Fixed => return Err(ExecutionErrorKind::TooFarLeft),
+                TapeMode::Bidirectional => {
+                    self.check_room()?;
+                    self.cells.push_front(0);
+                    self.origin += 1;
+                }
+                TapeMode::Circular => return Ok(self.highest_index()),
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Whether cell 0 lies on the tape and a fixed or circular tape still has its length, which
+    /// a deserialized tape does not guarantee.
+    pub(crate) fn is_consistent(&self, length: usize) -> bool {
+        let fixed = matches!(self.mode, TapeMode::Fixed | TapeMode::Circular);
+        self.origin < self.cells.len() && (!fixed || self.cells.len() == length.max(1))
+    }
+
+    /// Whether moving one cell at a time can reach every cell from `low` to `high`, growing the
+    /// tape if needed, without going past either end or the limit on the number of cells.
+    pub(crate) fn can_reach(&self, low: isize, high: isize) -> bool {
+        let grown = (self.lowest_index() - low).max(0) + (high - self.highest_index()).max(0);
+        let has_room = self
+            .max_cells
+            .is_none_or(|max_cells| self.cells.len() + grown as usize <= max_cells);
+
+        match self.mode {
+            TapeMode::Growable => low >= self.lowest_index() && has_room,
+            TapeMode::Bidirectional => has_room,
+            TapeMode::Fixed => self.contains(low) && self.contains(high),
+            TapeMode::Circular => true,
+        }
+    }
+
+    pub(crate) fn set_max_cells(&mut self, max_cells: Option<usize>) {
+        self.max_cells = max_cells;
+    }
+
+    fn check_room(&self) -> Result<(), ExecutionErrorKind> {
+        match self.max_cells {
+            Some(max_cells) if self.cells.len() >= max_cells => {
+                Err(ExecutionErrorKind::TapeLimitExceeded { cells: max_cells })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn position(&self, index: isize) -> Option<usize> {
+        if self.contains(index) {
+            Some((index + self.origin as isize) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Tape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let singular = self.len() == 1;
+        write!(
+            f,
+            "a {} tape of {} cell{}, from index {} to index {}",
+            self.mode,
+            self.len(),
+            if singular { "" } else { "s" },
+            self.lowest_index(),
+            self.highest_index()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::executor::execution_error_kind::ExecutionErrorKind;
+
+    use super::{Tape, TapeMode};
+
+    #[test]
+    fn bidirectional_tapes_grow_to_the_left() {
+        let mut tape = Tape::new(TapeMode::Bidirectional, 0);
+
+        assert_eq!(tape.move_left(0), Ok(-1));
+        assert_eq!(tape.move_left(-1), Ok(-2));
+        assert!(tape.set(-2, 7));
+        assert_eq!(tape.move_right(0), Ok(1));
+
+        assert_eq!((tape.lowest_index(), tape.highest_index()), (-2, 1));
+        assert_eq!(tape.get(-2), Some(7));
+        assert_eq!(tape.get(-3), None);
+        assert_eq!(
+            tape.iter().collect::<Vec<_>>(),
+            [(-2, 7), (-1, 0), (0, 0), (1, 0)]
+        );
+        assert_eq!(
+            tape.to_string(),
+            "a bidirectional tape of 4 cells, from index -2 to index 1"
+        );
+    }
+
+    #[test]
+    fn only_bidirectional_and_circular_tapes_go_left_of_cell_0() {
+        assert_eq!(
+            Tape::new(TapeMode::Growable, 0).move_left(0),
+            Err(ExecutionErrorKind::TooFarLeft)
+        );
+        assert_eq!(
+            Tape::new(TapeMode::Fixed, 3).move_left(0),
+            Err(ExecutionErrorKind::TooFarLeft)
+        );
+        assert_eq!(Tape::new(TapeMode::Circular, 3).move_left(0), Ok(2));
+        assert_eq!(Tape::new(TapeMode::Bidirectional, 0).move_left(0), Ok(-1));
+    }
+
+    #[test]
+    fn growing_in_either_direction_counts_towards_max_cells() {
+        let mut tape = Tape::with_max_cells(TapeMode::Bidirectional, 0, Some(3));
+
+        assert_eq!(tape.move_left(0), Ok(-1));
+        assert_eq!(tape.move_right(0), Ok(1));
+        assert_eq!(
+            tape.move_left(-1),
+            Err(ExecutionErrorKind::TapeLimitExceeded { cells: 3 })
+        );
+        assert_eq!(
+            tape.move_right(1),
+            Err(ExecutionErrorKind::TapeLimitExceeded { cells: 3 })
+        );
+        assert!(tape.can_reach(-1, 1));
+        assert!(!tape.can_reach(-2, 1));
+    }
+}
@@ -0,0 +1,118 @@
+/// A position in the machine code that jumps can refer to before it is known.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Label(usize);
+
+/// The condition of a conditional jump, named after the x86-64 `jcc` instructions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Condition {
+    Below,
+    AboveOrEqual,
+    Equal,
+    NotEqual,
+    BelowOrEqual,
+    Above,
+    Sign,
+    NotSign,
+    LessOrEqual,
+}
+
+impl Condition {
+    fn opcode(&self) -> u8 {
+        match self {
+            Condition::Below => 0x82,
+            Condition::AboveOrEqual => 0x83,
+            Condition::Equal => 0x84,
+            Condition::NotEqual => 0x85,
+            Condition::BelowOrEqual => 0x86,
+            Condition::Above => 0x87,
+            Condition::Sign => 0x88,
+            Condition::NotSign => 0x89,
+            Condition::LessOrEqual => 0x8e,
+        }
+    }
+}
+
+/// Collects x86-64 machine code and resolves jumps to labels once all of them are bound.
+#[derive(Default)]
+pub struct Assembler {
+    code: Vec<u8>,
+    labels: Vec<Option<usize>>,
+    fixups: Vec<(usize, Label)>, // the position of a rel32 operand and the label it jumps to
+}
+
+impl Assembler {
+    pub fn new() -> Assembler {
+        Assembler::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+
+    pub fn new_label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    /// Makes the label refer to the current end of the code.
+    pub fn bind(&mut self, label: Label) {
+        self.labels[label.0] = Some(self.code.len());
+    }
+
+    pub fn emit(&mut self, bytes: &[u8]) {
+        self.code.extend_from_slice(bytes);
+    }
+
+    pub fn emit_u16(&mut self, value: u16) {
+        self.emit(&value.to_le_bytes());
+    }
+
+    pub fn emit_u32(&mut self, value: u32) {
+        self.emit(&value.to_le_bytes());
+    }
+
+    pub fn emit_u64(&mut self, value: u64) {
+        self.emit(&value.to_le_bytes());
+    }
+
+    /// `jmp rel32`
+    pub fn jump(&mut self, label: Label) {
+        self.emit(&[0xe9]);
+        self.emit_relative(label);
+    }
+
+    /// `jcc rel32`
+    pub fn jump_if(&mut self, condition: Condition, label: Label) {
+        self.emit(&[0x0f, condition.opcode()]);
+        self.emit_relative(label);
+    }
+
+    /// `call rel32`
+    pub fn call(&mut self, label: Label) {
+        self.emit(&[0xe8]);
+        self.emit_relative(label);
+    }
+
+    /// Emits the 32-bit distance from the end of the operand to the label, as used by jumps, calls
+    /// and `rip`-relative operands ending an instruction.
+    pub fn emit_relative(&mut self, label: Label) {
+        self.fixups.push((self.code.len(), label));
+        self.emit_u32(0);
+    }
+
+    /// Resolves all jumps and gives back the machine code. Every label that is jumped to must have
+    /// been bound.
+    pub fn finish(mut self) -> Vec<u8> {
+        for (position, label) in self.fixups {
+            let target = self.labels[label.0].expect("jump to an unbound label");
+            let relative = target as i64 - (position as i64 + 4);
+            self.code[position..position + 4].copy_from_slice(&(relative as i32).to_le_bytes());
+        }
+
+        self.code
+    }
+}
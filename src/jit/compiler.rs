@@ -0,0 +1,427 @@
+use crate::executor::{
+    cell_type::CellType, executor_config::ExecutorConfig, executor_state::Int,
+    optimized_command::OptimizedCommand, overflow_policy::OverflowPolicy, tape::TapeMode,
+};
+
+use super::{
+    assembler::{Assembler, Condition, Label},
+    jit_error::JitError,
+};
+
+// The compiled code is a function `extern "C" fn(context: *mut Context) -> u64`, where the
+// context starts with the following fields, each 8 bytes wide.
+
+/// A pointer to the first cell of the tape.
+pub const CONTEXT_TAPE: u8 = 0;
+/// The number of cells on the tape.
+pub const CONTEXT_LENGTH: u8 = 8;
+/// `extern "C" fn(context, value: i64) -> u64`, writes the value and returns 0 on success.
+pub const CONTEXT_OUTPUT: u8 = 16;
+/// `extern "C" fn(context, value: i64, result: *mut i64) -> u64`, reads into `result` given the
+/// current value of the cell and returns 0 on success.
+pub const CONTEXT_INPUT: u8 = 24;
+/// `extern "C" fn(context, index: u64) -> u64`, grows the tape so that it contains the index,
+/// updates the tape and the length in the context and returns 0 on success.
+pub const CONTEXT_GROW: u8 = 32;
+/// Written by the compiled code when the program fails: the index of the optimized command that
+/// failed.
+pub const CONTEXT_FAILED_COMMAND: u8 = 40;
+/// Written by the compiled code when the program fails: the index of the current cell. A move
+/// that failed has already added its offset, a multiply loop that failed has not moved at all.
+pub const CONTEXT_FAILED_POINTER: u8 = 48;
+
+/// The value the compiled function returns when the program finished.
+pub const EXIT_FINISHED: u64 = 0;
+/// The value the compiled function returns when a callback failed.
+pub const EXIT_CALLBACK_FAILED: u64 = 1;
+/// The value the compiled function returns when the data pointer went too far to the left.
+pub const EXIT_TOO_FAR_LEFT: u64 = 2;
+/// The value the compiled function returns when the data pointer went too far to the right.
+pub const EXIT_TOO_FAR_RIGHT: u64 = 3;
+
+/// Checks whether a program with the configuration can be compiled into machine code. Cells have
+/// to wrap around and fit into a machine register, the tape can not grow to the left, and there
+/// can be no step limit or timeout.
+pub fn check_supported(config: &ExecutorConfig) -> Result<(), JitError> {
+    if config.cell_type == CellType::I128 {
+        return Err(JitError::UnsupportedCellType(config.cell_type));
+    }
+
+    if config.overflow_policy != OverflowPolicy::Wrap {
+        return Err(JitError::UnsupportedOverflowPolicy(config.overflow_policy));
+    }
+
+    if config.tape_mode == TapeMode::Bidirectional {
+        return Err(JitError::UnsupportedTapeMode(config.tape_mode));
+    }
+
+    if config.max_steps.is_some() || config.timeout.is_some() {
+        return Err(JitError::UnsupportedLimits);
+    }
+
+    Ok(())
+}
+
+/// Compiles an optimized program into x86-64 machine code.
+///
+/// While the program runs, `rbx` holds the pointer to the first cell, `r12` the index of the
+/// current cell, `r13` the context, `r14` the number of cells and `r15` the counter of a
+/// multiply loop.
+pub fn compile(
+    commands: &[OptimizedCommand],
+    config: &ExecutorConfig,
+) -> Result<Vec<u8>, JitError> {
+    check_supported(config)?;
+
+    let mut assembler = Assembler::new();
+    let mut compiler = Compiler {
+        exit_callback_failed: assembler.new_label(),
+        exit_too_far_left: assembler.new_label(),
+        exit_too_far_right: assembler.new_label(),
+        assembler,
+        cell_type: config.cell_type,
+        tape_mode: config.tape_mode,
+        index: 0,
+    };
+
+    compiler.prologue();
+
+    let after: Vec<Label> = commands
+        .iter()
+        .map(|_| compiler.assembler.new_label())
+        .collect();
+    for (index, command) in commands.iter().enumerate() {
+        compiler.index = u32::try_from(index).map_err(|_err| JitError::ProgramTooLarge)?;
+        compiler.command(command, &after)?;
+        compiler.assembler.bind(after[index]);
+    }
+
+    compiler.epilogue();
+
+    Ok(compiler.assembler.finish())
+}
+
+struct Compiler {
+    assembler: Assembler,
+    cell_type: CellType,
+    tape_mode: TapeMode,
+    exit_callback_failed: Label,
+    exit_too_far_left: Label,
+    exit_too_far_right: Label,
+    index: u32, // the index of the optimized command being compiled
+}
+
+impl Compiler {
+    fn command(&mut self, command: &OptimizedCommand, after: &[Label]) -> Result<(), JitError> {
+        match command {
+            OptimizedCommand::Add(delta) => self.add_to_cell(*delta),
+            OptimizedCommand::Move(offset) => self.move_pointer(*offset)?,
+            OptimizedCommand::Clear => self.clear_cell(),
+            OptimizedCommand::Scan(offset) => {
+                let start = self.assembler.new_label();
+                let done = self.assembler.new_label();
+                self.assembler.bind(start);
+                self.compare_cell_with_zero();
+                self.assembler.jump_if(Condition::Equal, done);
+                self.move_pointer(*offset)?;
+                self.assembler.jump(start);
+                self.assembler.bind(done);
+            }
+            OptimizedCommand::Multiply(factors) => {
+                let done = self.assembler.new_label();
+                self.compare_cell_with_zero();
+                self.assembler.jump_if(Condition::Equal, done);
+                // Makes sure every cell can be reached before changing any of them, so a loop
+                // that fails leaves the tape as it was.
+                if self.tape_mode != TapeMode::Circular {
+                    for (offset, _) in factors {
+                        self.check_reachable(*offset)?;
+                    }
+                }
+                self.load_cell();
+                self.assembler.emit(&[0x49, 0x89, 0xc7]); // mov r15, rax
+                for (offset, factor) in factors {
+                    self.move_pointer_unchecked(*offset)?;
+                    if let Ok(factor) = i32::try_from(*factor) {
+                        self.assembler.emit(&[0x49, 0x69, 0xc7]); // imul rax, r15, imm32
+                        self.assembler.emit_u32(factor as u32);
+                    } else {
+                        self.assembler.emit(&[0x48, 0xb8]); // mov rax, imm64
+                        self.assembler.emit_u64(*factor as u64);
+                        self.assembler.emit(&[0x49, 0x0f, 0xaf, 0xc7]); // imul rax, r15
+                    }
+                    self.add_rax_to_cell();
+                    self.move_pointer_unchecked(-offset)?;
+                }
+                self.clear_cell();
+                self.assembler.bind(done);
+            }
+            OptimizedCommand::Output => {
+                self.record_failure();
+                self.load_cell();
+                self.assembler.emit(&[0x48, 0x89, 0xc6]); // mov rsi, rax
+                self.call_context(CONTEXT_OUTPUT);
+            }
+            OptimizedCommand::Input => {
+                self.record_failure();
+                self.load_cell();
+                self.assembler.emit(&[0x48, 0x89, 0xc6]); // mov rsi, rax
+                self.assembler.emit(&[0x48, 0x89, 0xe2]); // mov rdx, rsp
+                self.call_context(CONTEXT_INPUT);
+                self.assembler.emit(&[0x48, 0x8b, 0x04, 0x24]); // mov rax, [rsp]
+                self.store_rax_to_cell();
+            }
+            OptimizedCommand::JumpForward(pos) => {
+                self.compare_cell_with_zero();
+                self.assembler.jump_if(Condition::Equal, after[*pos]);
+            }
+            OptimizedCommand::JumpBack(pos) => {
+                self.compare_cell_with_zero();
+                self.assembler.jump_if(Condition::NotEqual, after[*pos]);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn prologue(&mut self) {
+        self.assembler.emit(&[0x53]); // push rbx
+        self.assembler.emit(&[0x41, 0x54]); // push r12
+        self.assembler.emit(&[0x41, 0x55]); // push r13
+        self.assembler.emit(&[0x41, 0x56]); // push r14
+        self.assembler.emit(&[0x41, 0x57]); // push r15
+        self.assembler.emit(&[0x48, 0x83, 0xec, 0x10]); // sub rsp, 16
+        self.assembler.emit(&[0x49, 0x89, 0xfd]); // mov r13, rdi
+        self.reload_tape();
+        self.assembler.emit(&[0x45, 0x31, 0xe4]); // xor r12d, r12d
+    }
+
+    fn epilogue(&mut self) {
+        let done = self.assembler.new_label();
+
+        self.assembler.emit(&[0xb8]); // mov eax, imm32
+        self.assembler.emit_u32(EXIT_FINISHED as u32);
+        self.assembler.jump(done);
+
+        for (label, code) in [
+            (self.exit_callback_failed, EXIT_CALLBACK_FAILED),
+            (self.exit_too_far_left, EXIT_TOO_FAR_LEFT),
+            (self.exit_too_far_right, EXIT_TOO_FAR_RIGHT),
+        ] {
+            self.assembler.bind(label);
+            self.assembler.emit(&[0xb8]); // mov eax, imm32
+            self.assembler.emit_u32(code as u32);
+            self.assembler.jump(done);
+        }
+
+        self.assembler.bind(done);
+        self.assembler.emit(&[0x48, 0x83, 0xc4, 0x10]); // add rsp, 16
+        self.assembler.emit(&[0x41, 0x5f]); // pop r15
+        self.assembler.emit(&[0x41, 0x5e]); // pop r14
+        self.assembler.emit(&[0x41, 0x5d]); // pop r13
+        self.assembler.emit(&[0x41, 0x5c]); // pop r12
+        self.assembler.emit(&[0x5b]); // pop rbx
+        self.assembler.emit(&[0xc3]); // ret
+    }
+
+    fn reload_tape(&mut self) {
+        self.assembler.emit(&[0x49, 0x8b, 0x5d, CONTEXT_TAPE]); // mov rbx, [r13 + tape]
+        self.assembler.emit(&[0x4d, 0x8b, 0x75, CONTEXT_LENGTH]); // mov r14, [r13 + length]
+    }
+
+    /// Calls a callback of the context with `rsi` and `rdx` as its arguments, leaving the function
+    /// if it fails.
+    fn call_context(&mut self, callback: u8) {
+        self.assembler.emit(&[0x4c, 0x89, 0xef]); // mov rdi, r13
+        self.assembler.emit(&[0x41, 0xff, 0x55, callback]); // call [r13 + callback]
+        self.assembler.emit(&[0x48, 0x85, 0xc0]); // test rax, rax
+        self.assembler
+            .jump_if(Condition::NotEqual, self.exit_callback_failed);
+    }
+
+    /// Stores the index of the current command and of the current cell in the context, to tell
+    /// where the program failed if the code after it does.
+    fn record_failure(&mut self) {
+        self.assembler
+            .emit(&[0x49, 0xc7, 0x45, CONTEXT_FAILED_COMMAND]); // mov qword [r13 + command], imm32
+        self.assembler.emit_u32(self.index);
+        self.assembler
+            .emit(&[0x4d, 0x89, 0x65, CONTEXT_FAILED_POINTER]); // mov [r13 + pointer], r12
+    }
+
+    /// Moves the data pointer and makes sure it is still on the tape.
+    fn move_pointer(&mut self, offset: isize) -> Result<(), JitError> {
+        let offset = i32::try_from(offset).map_err(|_err| JitError::OffsetTooLarge)?;
+        let on_tape = self.assembler.new_label();
+
+        self.assembler.emit(&[0x49, 0x81, 0xc4]); // add r12, imm32
+        self.assembler.emit_u32(offset as u32);
+        self.assembler.emit(&[0x4d, 0x39, 0xf4]); // cmp r12, r14
+        self.assembler.jump_if(Condition::Below, on_tape);
+
+        match self.tape_mode {
+            TapeMode::Growable if offset > 0 => {
+                self.record_failure();
+                self.assembler.emit(&[0x4c, 0x89, 0xe6]); // mov rsi, r12
+                self.call_context(CONTEXT_GROW);
+                self.reload_tape();
+            }
+            TapeMode::Circular => {
+                // r12 = r12 mod r14, rounded towards negative infinity
+                self.assembler.emit(&[0x4c, 0x89, 0xe0]); // mov rax, r12
+                self.assembler.emit(&[0x48, 0x99]); // cqo
+                self.assembler.emit(&[0x49, 0xf7, 0xfe]); // idiv r14
+                self.assembler.emit(&[0x49, 0x89, 0xd4]); // mov r12, rdx
+                self.assembler.emit(&[0x4d, 0x85, 0xe4]); // test r12, r12
+                self.assembler.jump_if(Condition::NotSign, on_tape);
+                self.assembler.emit(&[0x4d, 0x01, 0xf4]); // add r12, r14
+            }
+            _ => {
+                self.record_failure();
+                if offset < 0 {
+                    self.assembler.jump(self.exit_too_far_left);
+                } else {
+                    self.assembler.jump(self.exit_too_far_right);
+                }
+            }
+        }
+
+        self.assembler.bind(on_tape);
+
+        Ok(())
+    }
+
+    /// Moves the data pointer, which has to stay on the tape.
+    fn move_pointer_unchecked(&mut self, offset: isize) -> Result<(), JitError> {
+        if self.tape_mode == TapeMode::Circular {
+            return self.move_pointer(offset);
+        }
+
+        let offset = i32::try_from(offset).map_err(|_err| JitError::OffsetTooLarge)?;
+        self.assembler.emit(&[0x49, 0x81, 0xc4]); // add r12, imm32
+        self.assembler.emit_u32(offset as u32);
+
+        Ok(())
+    }
+
+    /// Makes sure the cell at the offset from the current one is on the tape, growing it if
+    /// needed, without moving the data pointer.
+    fn check_reachable(&mut self, offset: isize) -> Result<(), JitError> {
+        let offset = i32::try_from(offset).map_err(|_err| JitError::OffsetTooLarge)?;
+        let on_tape = self.assembler.new_label();
+
+        self.assembler.emit(&[0x49, 0x8d, 0x84, 0x24]); // lea rax, [r12 + imm32]
+        self.assembler.emit_u32(offset as u32);
+        self.assembler.emit(&[0x4c, 0x39, 0xf0]); // cmp rax, r14
+        self.assembler.jump_if(Condition::Below, on_tape);
+
+        self.record_failure();
+        match self.tape_mode {
+            TapeMode::Growable if offset > 0 => {
+                self.assembler.emit(&[0x48, 0x89, 0xc6]); // mov rsi, rax
+                self.call_context(CONTEXT_GROW);
+                self.reload_tape();
+            }
+            _ if offset < 0 => self.assembler.jump(self.exit_too_far_left),
+            _ => self.assembler.jump(self.exit_too_far_right),
+        }
+
+        self.assembler.bind(on_tape);
+
+        Ok(())
+    }
+
+    fn add_to_cell(&mut self, delta: Int) {
+        match self.cell_type.size() {
+            1 => {
+                self.cell_instruction(&[0x80], 0); // add byte [cell], imm8
+                self.assembler.emit(&[delta as u8]);
+            }
+            2 => {
+                self.cell_instruction(&[0x81], 0); // add word [cell], imm16
+                self.assembler.emit_u16(delta as u16);
+            }
+            4 => {
+                self.cell_instruction(&[0x81], 0); // add dword [cell], imm32
+                self.assembler.emit_u32(delta as u32);
+            }
+            _ => {
+                self.assembler.emit(&[0x48, 0xb8]); // mov rax, imm64
+                self.assembler.emit_u64(delta as u64);
+                self.add_rax_to_cell();
+            }
+        }
+    }
+
+    fn add_rax_to_cell(&mut self) {
+        match self.cell_type.size() {
+            1 => self.cell_instruction(&[0x00], 0), // add [cell], al
+            _ => self.cell_instruction(&[0x01], 0), // add [cell], ax / eax / rax
+        }
+    }
+
+    fn clear_cell(&mut self) {
+        match self.cell_type.size() {
+            1 => {
+                self.cell_instruction(&[0xc6], 0); // mov byte [cell], imm8
+                self.assembler.emit(&[0]);
+            }
+            2 => {
+                self.cell_instruction(&[0xc7], 0); // mov word [cell], imm16
+                self.assembler.emit_u16(0);
+            }
+            _ => {
+                self.cell_instruction(&[0xc7], 0); // mov dword / qword [cell], imm32
+                self.assembler.emit_u32(0);
+            }
+        }
+    }
+
+    fn compare_cell_with_zero(&mut self) {
+        match self.cell_type.size() {
+            1 => self.cell_instruction(&[0x80], 7), // cmp byte [cell], imm8
+            _ => self.cell_instruction(&[0x83], 7), // cmp word / dword / qword [cell], imm8
+        }
+        self.assembler.emit(&[0]);
+    }
+
+    /// Loads the current cell into `rax`, extending it to 64 bits according to its signedness.
+    fn load_cell(&mut self) {
+        match self.cell_type {
+            CellType::U8 => self.cell_instruction(&[0x0f, 0xb6], 0), // movzx eax, byte [cell]
+            CellType::U16 => self.cell_instruction(&[0x0f, 0xb7], 0), // movzx eax, word [cell]
+            CellType::U32 => self.cell_instruction(&[0x8b], 0),      // mov eax, dword [cell]
+            CellType::I32 => self.cell_instruction(&[0x63], 0),      // movsxd rax, dword [cell]
+            _ => self.cell_instruction(&[0x8b], 0),                  // mov rax, qword [cell]
+        }
+    }
+
+    fn store_rax_to_cell(&mut self) {
+        match self.cell_type.size() {
+            1 => self.cell_instruction(&[0x88], 0), // mov [cell], al
+            _ => self.cell_instruction(&[0x89], 0), // mov [cell], ax / eax / rax
+        }
+    }
+
+    /// Emits an instruction whose memory operand is the current cell, `[rbx + r12 * size]`, with
+    /// `reg` as the register or the opcode extension in the ModRM byte. The operand size follows
+    /// the cell type, except for `movsxd` and `movzx` which always load into a 32 or 64 bit
+    /// register.
+    fn cell_instruction(&mut self, opcode: &[u8], reg: u8) {
+        let size = self.cell_type.size();
+        let loads_extended = matches!(opcode, [0x0f, 0xb6] | [0x0f, 0xb7]);
+
+        if size == 2 && !loads_extended {
+            self.assembler.emit(&[0x66]); // operand size prefix
+        }
+
+        let wide = size == 8 || opcode == [0x63];
+        let rex = 0x40 | (if wide { 0x08 } else { 0 }) | 0x02; // REX.W and REX.X for r12
+        self.assembler.emit(&[rex]);
+        self.assembler.emit(opcode);
+
+        let scale = size.trailing_zeros() as u8;
+        self.assembler.emit(&[(reg << 3) | 0b100]); // ModRM: [SIB]
+        self.assembler.emit(&[(scale << 6) | (0b100 << 3) | 0b011]); // SIB: rbx + r12 * size
+    }
+}
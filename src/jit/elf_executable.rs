@@ -0,0 +1,674 @@
+use crate::executor::{
+    eof_policy::EofPolicy, execution_error_kind::ExecutionErrorKind,
+    executor_command::ExecutorCommand, executor_config::ExecutorConfig, io_mode::IoMode,
+    optimized_command::optimize, tape::TapeMode,
+};
+
+use super::{
+    assembler::{Assembler, Condition, Label},
+    compiler::{
+        CONTEXT_GROW, CONTEXT_INPUT, CONTEXT_LENGTH, CONTEXT_OUTPUT, CONTEXT_TAPE,
+        EXIT_TOO_FAR_LEFT, EXIT_TOO_FAR_RIGHT, compile,
+    },
+    jit_error::JitError,
+};
+
+// The executable has two segments: the code, which starts right after the headers, and the
+// zeroed data, which holds the context of the compiled function followed by the output buffer.
+
+const CODE_ADDRESS: u64 = 0x400000;
+const DATA_ADDRESS: u64 = 0x600000;
+
+const ELF_HEADER_SIZE: u64 = 64;
+const PROGRAM_HEADER_SIZE: u64 = 56;
+const HEADERS_SIZE: u64 = ELF_HEADER_SIZE + 2 * PROGRAM_HEADER_SIZE;
+
+// The fields the runtime adds to the context, after the ones the compiled code uses.
+
+/// A pointer to the message of the error that stopped the program.
+const CONTEXT_MESSAGE: u8 = 56;
+/// The length of the message.
+const CONTEXT_MESSAGE_LENGTH: u8 = 64;
+/// The number of bytes in the output buffer.
+const CONTEXT_BUFFERED: u8 = 72;
+/// A byte read from the input.
+const CONTEXT_READ: u8 = 80;
+/// The start of the output buffer.
+const CONTEXT_BUFFER: u8 = 88;
+
+const BUFFER_SIZE: u32 = 4096;
+
+/// The tape of a growable tape is reserved up front, but only backed by memory once used.
+const GROWABLE_TAPE_SIZE: u64 = 1 << 36;
+
+const SYS_READ: u32 = 0;
+const SYS_WRITE: u32 = 1;
+const SYS_MMAP: u32 = 9;
+const SYS_EXIT: u32 = 60;
+const STDOUT: u32 = 1;
+const STDERR: u32 = 2;
+const EINTR: u8 = 4;
+
+const MEMORY_ERROR: &str = "Memory Error: Unable to grow the tape!";
+
+/// Builds a static x86-64 Linux executable, which runs the program like `ExecutorState` with the
+/// same configuration, reading from the standard input and writing to the standard output. The
+/// executable needs no libraries, it talks to the kernel through system calls only. When the
+/// program fails, the executable writes the error to the standard error and exits with status 1.
+pub fn build_executable(
+    commands: &[ExecutorCommand],
+    config: &ExecutorConfig,
+) -> Result<Vec<u8>, JitError> {
+    let program = compile(&optimize(commands, config), config)?;
+
+    let mut assembler = Assembler::new();
+    let mut builder = Builder {
+        labels: Labels {
+            program: assembler.new_label(),
+            output: assembler.new_label(),
+            input: assembler.new_label(),
+            grow: assembler.new_label(),
+            put_byte: assembler.new_label(),
+            flush: assembler.new_label(),
+            read_byte: assembler.new_label(),
+            messages: Vec::new(),
+        },
+        assembler,
+        config: *config,
+    };
+
+    builder.start();
+    builder.output();
+    builder.input();
+    builder.grow();
+    builder.put_byte();
+    builder.flush();
+    builder.read_byte();
+
+    builder.assembler.bind(builder.labels.program);
+    builder.assembler.emit(&program);
+    for (label, message) in std::mem::take(&mut builder.labels.messages) {
+        builder.assembler.bind(label);
+        builder.assembler.emit(message.as_bytes());
+    }
+
+    let code = builder.assembler.finish();
+    let mut bytes = Vec::new();
+    write_elf_header(&mut bytes);
+    // The code segment maps the file from its start, so the headers are mapped as well.
+    write_program_header(
+        &mut bytes,
+        5, // readable and executable
+        0,
+        CODE_ADDRESS,
+        HEADERS_SIZE + code.len() as u64,
+        HEADERS_SIZE + code.len() as u64,
+    );
+    write_program_header(
+        &mut bytes,
+        6, // readable and writable
+        0,
+        DATA_ADDRESS,
+        0,
+        CONTEXT_BUFFER as u64 + BUFFER_SIZE as u64,
+    );
+    bytes.extend(code);
+
+    Ok(bytes)
+}
+
+fn write_elf_header(bytes: &mut Vec<u8>) {
+    bytes.extend(b"\x7fELF");
+    bytes.extend([2, 1, 1, 0]); // 64-bit, little endian, version 1, System V
+    bytes.extend([0; 8]);
+    bytes.extend(2u16.to_le_bytes()); // an executable
+    bytes.extend(0x3eu16.to_le_bytes()); // x86-64
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend((CODE_ADDRESS + HEADERS_SIZE).to_le_bytes()); // the entry point
+    bytes.extend(ELF_HEADER_SIZE.to_le_bytes()); // the program headers
+    bytes.extend(0u64.to_le_bytes()); // no section headers
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend((ELF_HEADER_SIZE as u16).to_le_bytes());
+    bytes.extend((PROGRAM_HEADER_SIZE as u16).to_le_bytes());
+    bytes.extend(2u16.to_le_bytes());
+    bytes.extend(64u16.to_le_bytes());
+    bytes.extend(0u16.to_le_bytes());
+    bytes.extend(0u16.to_le_bytes());
+}
+
+fn write_program_header(
+    bytes: &mut Vec<u8>,
+    flags: u32,
+    offset: u64,
+    address: u64,
+    file_size: u64,
+    memory_size: u64,
+) {
+    bytes.extend(1u32.to_le_bytes()); // a loadable segment
+    bytes.extend(flags.to_le_bytes());
+    bytes.extend(offset.to_le_bytes());
+    bytes.extend(address.to_le_bytes());
+    bytes.extend(address.to_le_bytes());
+    bytes.extend(file_size.to_le_bytes());
+    bytes.extend(memory_size.to_le_bytes());
+    bytes.extend(0x1000u64.to_le_bytes());
+}
+
+struct Labels {
+    program: Label,
+    output: Label,
+    input: Label,
+    grow: Label,
+    put_byte: Label,
+    flush: Label,
+    read_byte: Label,
+    messages: Vec<(Label, String)>,
+}
+
+/// Writes the runtime around the compiled function. Apart from `start`, the routines take the
+/// context in `rdi`, keep it there and return 0 in `rax` on success. On failure they store the
+/// message of the error in the context and return 1.
+struct Builder {
+    assembler: Assembler,
+    labels: Labels,
+    config: ExecutorConfig,
+}
+
+impl Builder {
+    /// The entry point, which maps the tape, fills in the context, runs the compiled function and
+    /// exits.
+    fn start(&mut self) {
+        let report = self.assembler.new_label();
+        let finished = self.assembler.new_label();
+        let not_too_far_left = self.assembler.new_label();
+        let no_memory = self.assembler.new_label();
+
+        let cell_size = self.config.cell_type.size() as u64;
+        let (length, tape_size) = match self.config.tape_mode {
+            TapeMode::Growable => (1, GROWABLE_TAPE_SIZE),
+            _ => {
+                let length = self.config.tape_length.max(1) as u64;
+                (length, length.saturating_mul(cell_size))
+            }
+        };
+
+        self.assembler.emit(&[0xbb]); // mov ebx, imm32
+        self.assembler.emit_u32(DATA_ADDRESS as u32);
+
+        // mmap(NULL, size, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS | MAP_NORESERVE,
+        //      -1, 0)
+        self.assembler.emit(&[0x31, 0xff]); // xor edi, edi
+        self.assembler.emit(&[0x48, 0xbe]); // mov rsi, imm64
+        self.assembler.emit_u64(tape_size);
+        self.assembler.emit(&[0xba]); // mov edx, imm32
+        self.assembler.emit_u32(0x3);
+        self.assembler.emit(&[0x41, 0xba]); // mov r10d, imm32
+        self.assembler.emit_u32(0x4022);
+        self.assembler
+            .emit(&[0x49, 0xc7, 0xc0, 0xff, 0xff, 0xff, 0xff]); // mov r8, -1
+        self.assembler.emit(&[0x45, 0x31, 0xc9]); // xor r9d, r9d
+        self.system_call(SYS_MMAP);
+        self.assembler.emit(&[0x48, 0x89, 0xdf]); // mov rdi, rbx
+        self.assembler.emit(&[0x48, 0x3d, 0x01, 0xf0, 0xff, 0xff]); // cmp rax, -4095
+        self.assembler.jump_if(Condition::AboveOrEqual, no_memory);
+
+        self.assembler.emit(&[0x48, 0x89, 0x43, CONTEXT_TAPE]); // mov [rbx + tape], rax
+        self.assembler.emit(&[0x48, 0xb8]); // mov rax, imm64
+        self.assembler.emit_u64(length);
+        self.assembler.emit(&[0x48, 0x89, 0x43, CONTEXT_LENGTH]); // mov [rbx + length], rax
+        for (label, field) in [
+            (self.labels.output, CONTEXT_OUTPUT),
+            (self.labels.input, CONTEXT_INPUT),
+            (self.labels.grow, CONTEXT_GROW),
+        ] {
+            self.assembler.emit(&[0x48, 0x8d, 0x05]); // lea rax, [rip + label]
+            self.assembler.emit_relative(label);
+            self.assembler.emit(&[0x48, 0x89, 0x43, field]); // mov [rbx + field], rax
+        }
+
+        self.assembler.call(self.labels.program);
+        self.assembler.emit(&[0x48, 0x89, 0xdf]); // mov rdi, rbx
+        self.assembler.emit(&[0x48, 0x85, 0xc0]); // test rax, rax
+        self.assembler.jump_if(Condition::Equal, finished);
+        self.assembler
+            .emit(&[0x48, 0x83, 0xf8, EXIT_TOO_FAR_LEFT as u8]); // cmp rax, imm8
+        self.assembler
+            .jump_if(Condition::NotEqual, not_too_far_left);
+        self.set_message(&ExecutionErrorKind::TooFarLeft.to_string());
+        self.assembler.jump(report);
+        self.assembler.bind(not_too_far_left);
+        self.assembler
+            .emit(&[0x48, 0x83, 0xf8, EXIT_TOO_FAR_RIGHT as u8]); // cmp rax, imm8
+        // Otherwise a callback failed, which has stored its message already.
+        self.assembler.jump_if(Condition::NotEqual, report);
+        self.set_message(&ExecutionErrorKind::TooFarRight.to_string());
+        self.assembler.jump(report);
+        self.assembler.bind(no_memory);
+        self.set_message(MEMORY_ERROR);
+        self.assembler.jump(report);
+
+        self.assembler.bind(finished);
+        self.assembler.call(self.labels.flush);
+        self.assembler.emit(&[0x85, 0xc0]); // test eax, eax
+        self.assembler.jump_if(Condition::NotEqual, report);
+        self.assembler.emit(&[0x31, 0xff]); // xor edi, edi
+        self.system_call(SYS_EXIT);
+
+        // Writes the output so far, then the message to the standard error.
+        self.assembler.bind(report);
+        self.assembler.call(self.labels.flush);
+        self.assembler.emit(&[0x48, 0x8b, 0x73, CONTEXT_MESSAGE]); // mov rsi, [rbx + message]
+        self.assembler
+            .emit(&[0x48, 0x8b, 0x53, CONTEXT_MESSAGE_LENGTH]); // mov rdx, [rbx + length]
+        self.assembler.emit(&[0xbf]); // mov edi, imm32
+        self.assembler.emit_u32(STDERR);
+        self.system_call(SYS_WRITE);
+        self.assembler.emit(&[0xbf]); // mov edi, imm32
+        self.assembler.emit_u32(1);
+        self.system_call(SYS_EXIT);
+    }
+
+    /// The output callback, which writes the value in `rsi`.
+    fn output(&mut self) {
+        self.assembler.bind(self.labels.output);
+
+        if self.config.io_mode == IoMode::Byte {
+            self.assembler.emit(&[0x89, 0xf0]); // mov eax, esi
+            self.assembler.jump(self.labels.put_byte);
+            return;
+        }
+
+        let invalid = self.assembler.new_label();
+        let multiple_bytes = self.assembler.new_label();
+        let three_bytes = self.assembler.new_label();
+        let two_bytes = self.assembler.new_label();
+        let lead = self.assembler.new_label();
+        let continuation = self.assembler.new_label();
+        let done = self.assembler.new_label();
+
+        // Negative values compare as huge ones, surrogates fall into [0, 0x7ff] once shifted.
+        self.assembler.emit(&[0x48, 0x81, 0xfe]); // cmp rsi, imm32
+        self.assembler.emit_u32(0x10ffff);
+        self.assembler.jump_if(Condition::Above, invalid);
+        self.assembler.emit(&[0x8d, 0x86]); // lea eax, [rsi + imm32]
+        self.assembler.emit_u32(-0xd800i32 as u32);
+        self.assembler.emit(&[0x3d]); // cmp eax, imm32
+        self.assembler.emit_u32(0x7ff);
+        self.assembler.jump_if(Condition::BelowOrEqual, invalid);
+        self.assembler.emit(&[0x81, 0xfe]); // cmp esi, imm32
+        self.assembler.emit_u32(0x80);
+        self.assembler
+            .jump_if(Condition::AboveOrEqual, multiple_bytes);
+        self.assembler.emit(&[0x89, 0xf0]); // mov eax, esi
+        self.assembler.jump(self.labels.put_byte);
+
+        // UTF-8 encoding, with the value in rbx and the shift of the next six bits in r12.
+        self.assembler.bind(multiple_bytes);
+        self.assembler.emit(&[0x53]); // push rbx
+        self.assembler.emit(&[0x41, 0x54]); // push r12
+        self.assembler.emit(&[0x48, 0x89, 0xf3]); // mov rbx, rsi
+        self.assembler.emit(&[0x81, 0xfb]); // cmp ebx, imm32
+        self.assembler.emit_u32(0x800);
+        self.assembler.jump_if(Condition::Below, two_bytes);
+        self.assembler.emit(&[0x81, 0xfb]); // cmp ebx, imm32
+        self.assembler.emit_u32(0x10000);
+        self.assembler.jump_if(Condition::Below, three_bytes);
+        for (label, shift, prefix) in [
+            (None, 18, 0xf0),
+            (Some(three_bytes), 12, 0xe0),
+            (Some(two_bytes), 6, 0xc0),
+        ] {
+            if let Some(label) = label {
+                self.assembler.bind(label);
+            }
+            self.assembler.emit(&[0x41, 0xbc]); // mov r12d, imm32
+            self.assembler.emit_u32(shift);
+            self.assembler.emit(&[0xb8]); // mov eax, imm32
+            self.assembler.emit_u32(prefix);
+            if shift != 6 {
+                self.assembler.jump(lead);
+            }
+        }
+
+        self.assembler.bind(lead);
+        self.assembler.emit(&[0x89, 0xda]); // mov edx, ebx
+        self.assembler.emit(&[0x44, 0x89, 0xe1]); // mov ecx, r12d
+        self.assembler.emit(&[0xd3, 0xea]); // shr edx, cl
+        self.assembler.emit(&[0x09, 0xd0]); // or eax, edx
+        self.assembler.call(self.labels.put_byte);
+        self.assembler.emit(&[0x85, 0xc0]); // test eax, eax
+        self.assembler.jump_if(Condition::NotEqual, done);
+
+        self.assembler.bind(continuation);
+        self.assembler.emit(&[0x41, 0x83, 0xec, 0x06]); // sub r12d, 6
+        self.assembler.emit(&[0x89, 0xd8]); // mov eax, ebx
+        self.assembler.emit(&[0x44, 0x89, 0xe1]); // mov ecx, r12d
+        self.assembler.emit(&[0xd3, 0xe8]); // shr eax, cl
+        self.assembler.emit(&[0x83, 0xe0, 0x3f]); // and eax, 0x3f
+        self.assembler.emit(&[0x0d]); // or eax, imm32
+        self.assembler.emit_u32(0x80);
+        self.assembler.call(self.labels.put_byte);
+        self.assembler.emit(&[0x85, 0xc0]); // test eax, eax
+        self.assembler.jump_if(Condition::NotEqual, done);
+        self.assembler.emit(&[0x45, 0x85, 0xe4]); // test r12d, r12d
+        self.assembler.jump_if(Condition::NotEqual, continuation);
+
+        self.assembler.bind(done);
+        self.assembler.emit(&[0x41, 0x5c]); // pop r12
+        self.assembler.emit(&[0x5b]); // pop rbx
+        self.assembler.emit(&[0xc3]); // ret
+
+        self.assembler.bind(invalid);
+        self.fail(&ExecutionErrorKind::InvalidCharacter.to_string());
+    }
+
+    /// The input callback, which flushes the output, reads a character and stores it at `rdx`,
+    /// given the current value of the cell in `rsi`.
+    fn input(&mut self) {
+        let done = self.assembler.new_label();
+        let failed = self.assembler.new_label();
+        let end_of_input = self.assembler.new_label();
+        let store = self.assembler.new_label();
+
+        // rbx holds the pointer to the result and r12 the current value, or the first byte of a
+        // UTF-8 sequence once one is read.
+        self.assembler.bind(self.labels.input);
+        self.assembler.emit(&[0x53]); // push rbx
+        self.assembler.emit(&[0x41, 0x54]); // push r12
+        self.assembler.emit(&[0x41, 0x55]); // push r13
+        self.assembler.emit(&[0x41, 0x56]); // push r14
+        self.assembler.emit(&[0x41, 0x57]); // push r15
+        self.assembler.emit(&[0x48, 0x89, 0xd3]); // mov rbx, rdx
+        self.assembler.emit(&[0x49, 0x89, 0xf4]); // mov r12, rsi
+        self.assembler.call(self.labels.flush);
+        self.assembler.emit(&[0x85, 0xc0]); // test eax, eax
+        self.assembler.jump_if(Condition::NotEqual, done);
+        self.read_byte_checked(failed, end_of_input);
+
+        if self.config.io_mode == IoMode::Unicode {
+            self.decode_utf8(failed, store);
+        }
+
+        self.assembler.bind(store);
+        self.assembler.emit(&[0x48, 0x89, 0x03]); // mov [rbx], rax
+        self.assembler.emit(&[0x31, 0xc0]); // xor eax, eax
+
+        self.assembler.bind(done);
+        self.assembler.emit(&[0x41, 0x5f]); // pop r15
+        self.assembler.emit(&[0x41, 0x5e]); // pop r14
+        self.assembler.emit(&[0x41, 0x5d]); // pop r13
+        self.assembler.emit(&[0x41, 0x5c]); // pop r12
+        self.assembler.emit(&[0x5b]); // pop rbx
+        self.assembler.emit(&[0xc3]); // ret
+
+        self.assembler.bind(failed);
+        self.assembler.emit(&[0xb8]); // mov eax, imm32
+        self.assembler.emit_u32(1);
+        self.assembler.jump(done);
+
+        self.assembler.bind(end_of_input);
+        match self.config.eof_policy {
+            EofPolicy::Zero => {
+                self.assembler.emit(&[0x48, 0xc7, 0x03]); // mov qword [rbx], imm32
+                self.assembler.emit_u32(0);
+            }
+            EofPolicy::MinusOne => {
+                self.assembler.emit(&[0x48, 0xc7, 0x03]); // mov qword [rbx], imm32
+                self.assembler.emit_u32(u32::MAX);
+            }
+            EofPolicy::Unchanged => self.assembler.emit(&[0x4c, 0x89, 0x23]), // mov [rbx], r12
+            EofPolicy::Error => {
+                self.set_message(&ExecutionErrorKind::InputError.to_string());
+                self.assembler.jump(failed);
+                return;
+            }
+        }
+        self.assembler.emit(&[0x31, 0xc0]); // xor eax, eax
+        self.assembler.jump(done);
+    }
+
+    /// Reads the rest of a UTF-8 sequence whose first byte is in `eax`, leaving the character in
+    /// `rax` and jumping to `store`. Only the shortest encodings of characters other than
+    /// surrogates are valid, like for `str::from_utf8`.
+    fn decode_utf8(&mut self, failed: Label, store: Label) {
+        let invalid = self.assembler.new_label();
+        let three_bytes = self.assembler.new_label();
+        let two_bytes = self.assembler.new_label();
+        let decode = self.assembler.new_label();
+        let next = self.assembler.new_label();
+        let check = self.assembler.new_label();
+
+        self.assembler.emit(&[0x3d]); // cmp eax, imm32
+        self.assembler.emit_u32(0x80);
+        self.assembler.jump_if(Condition::Below, store);
+        self.assembler.emit(&[0x41, 0x89, 0xc4]); // mov r12d, eax
+        for (bound, condition, label) in [
+            (0xc2, Condition::Below, invalid),
+            (0xe0, Condition::Below, two_bytes),
+            (0xf0, Condition::Below, three_bytes),
+            (0xf5, Condition::AboveOrEqual, invalid),
+        ] {
+            self.assembler.emit(&[0x3d]); // cmp eax, imm32
+            self.assembler.emit_u32(bound);
+            self.assembler.jump_if(condition, label);
+        }
+
+        // r13 collects the bits of the character, r14 holds the number of continuation bytes and
+        // r15 counts the ones read so far.
+        for (label, count, mask) in [
+            (None, 3, 0x07),
+            (Some(three_bytes), 2, 0x0f),
+            (Some(two_bytes), 1, 0x1f),
+        ] {
+            if let Some(label) = label {
+                self.assembler.bind(label);
+            }
+            self.assembler.emit(&[0x41, 0xbe]); // mov r14d, imm32
+            self.assembler.emit_u32(count);
+            self.assembler.emit(&[0x83, 0xe0, mask]); // and eax, imm8
+            if count != 1 {
+                self.assembler.jump(decode);
+            }
+        }
+
+        self.assembler.bind(decode);
+        self.assembler.emit(&[0x41, 0x89, 0xc5]); // mov r13d, eax
+        self.assembler.emit(&[0x45, 0x31, 0xff]); // xor r15d, r15d
+
+        // The input running out in the middle of a sequence makes it invalid.
+        self.assembler.bind(next);
+        self.read_byte_checked(failed, invalid);
+        self.assembler.emit(&[0xb9]); // mov ecx, imm32
+        self.assembler.emit_u32(0x80);
+        self.assembler.emit(&[0xba]); // mov edx, imm32
+        self.assembler.emit_u32(0xbf);
+        self.assembler.emit(&[0x4d, 0x85, 0xff]); // test r15, r15
+        self.assembler.jump_if(Condition::NotEqual, check);
+        // The second byte has a narrower range after some first bytes.
+        for (first, register, bound) in [
+            (0xe0, 0xb9, 0xa0),
+            (0xed, 0xba, 0x9f),
+            (0xf0, 0xb9, 0x90),
+            (0xf4, 0xba, 0x8f),
+        ] {
+            let other = self.assembler.new_label();
+            self.assembler.emit(&[0x41, 0x81, 0xfc]); // cmp r12d, imm32
+            self.assembler.emit_u32(first);
+            self.assembler.jump_if(Condition::NotEqual, other);
+            self.assembler.emit(&[register]); // mov ecx / edx, imm32
+            self.assembler.emit_u32(bound);
+            self.assembler.bind(other);
+        }
+
+        self.assembler.bind(check);
+        self.assembler.emit(&[0x39, 0xc8]); // cmp eax, ecx
+        self.assembler.jump_if(Condition::Below, invalid);
+        self.assembler.emit(&[0x39, 0xd0]); // cmp eax, edx
+        self.assembler.jump_if(Condition::Above, invalid);
+        self.assembler.emit(&[0x49, 0xc1, 0xe5, 0x06]); // shl r13, 6
+        self.assembler.emit(&[0x83, 0xe0, 0x3f]); // and eax, 0x3f
+        self.assembler.emit(&[0x49, 0x09, 0xc5]); // or r13, rax
+        self.assembler.emit(&[0x49, 0xff, 0xc7]); // inc r15
+        self.assembler.emit(&[0x4d, 0x39, 0xf7]); // cmp r15, r14
+        self.assembler.jump_if(Condition::Below, next);
+        self.assembler.emit(&[0x4c, 0x89, 0xe8]); // mov rax, r13
+        self.assembler.jump(store);
+
+        self.assembler.bind(invalid);
+        self.set_message(&ExecutionErrorKind::InvalidInput.to_string());
+        self.assembler.jump(failed);
+    }
+
+    /// Calls `read_byte`, jumping to `failed` if reading failed and to `end_of_input` if the input
+    /// has run out.
+    fn read_byte_checked(&mut self, failed: Label, end_of_input: Label) {
+        self.assembler.call(self.labels.read_byte);
+        self.assembler.emit(&[0x48, 0x83, 0xf8, 0xfe]); // cmp rax, -2
+        self.assembler.jump_if(Condition::Equal, failed);
+        self.assembler.emit(&[0x48, 0x85, 0xc0]); // test rax, rax
+        self.assembler.jump_if(Condition::Sign, end_of_input);
+    }
+
+    /// The grow callback. The whole tape is mapped already, so only its length changes.
+    fn grow(&mut self) {
+        let full = self.assembler.new_label();
+
+        self.assembler.bind(self.labels.grow);
+        self.assembler.emit(&[0x48, 0xb8]); // mov rax, imm64
+        self.assembler
+            .emit_u64(GROWABLE_TAPE_SIZE / self.config.cell_type.size() as u64);
+        self.assembler.emit(&[0x48, 0x39, 0xc6]); // cmp rsi, rax
+        self.assembler.jump_if(Condition::AboveOrEqual, full);
+        self.assembler.emit(&[0x48, 0x8d, 0x46, 0x01]); // lea rax, [rsi + 1]
+        self.assembler.emit(&[0x48, 0x89, 0x47, CONTEXT_LENGTH]); // mov [rdi + length], rax
+        self.assembler.emit(&[0x31, 0xc0]); // xor eax, eax
+        self.assembler.emit(&[0xc3]); // ret
+
+        self.assembler.bind(full);
+        self.fail(MEMORY_ERROR);
+    }
+
+    /// Appends the byte in `al` to the output buffer, flushing it once it is full.
+    fn put_byte(&mut self) {
+        self.assembler.bind(self.labels.put_byte);
+        self.assembler.emit(&[0x48, 0x8b, 0x4f, CONTEXT_BUFFERED]); // mov rcx, [rdi + buffered]
+        self.assembler.emit(&[0x88, 0x44, 0x0f, CONTEXT_BUFFER]); // mov [rdi + rcx + buffer], al
+        self.assembler.emit(&[0x48, 0xff, 0xc1]); // inc rcx
+        self.assembler.emit(&[0x48, 0x89, 0x4f, CONTEXT_BUFFERED]); // mov [rdi + buffered], rcx
+        self.assembler.emit(&[0x48, 0x81, 0xf9]); // cmp rcx, imm32
+        self.assembler.emit_u32(BUFFER_SIZE);
+        self.assembler
+            .jump_if(Condition::AboveOrEqual, self.labels.flush);
+        self.assembler.emit(&[0x31, 0xc0]); // xor eax, eax
+        self.assembler.emit(&[0xc3]); // ret
+    }
+
+    /// Writes the output buffer to the standard output and empties it.
+    fn flush(&mut self) {
+        let next = self.assembler.new_label();
+        let done = self.assembler.new_label();
+        let failed = self.assembler.new_label();
+
+        // r9 holds the context while rdi is needed for the system call, r8 counts the bytes
+        // written.
+        self.assembler.bind(self.labels.flush);
+        self.assembler.emit(&[0x49, 0x89, 0xf9]); // mov r9, rdi
+        self.assembler.emit(&[0x45, 0x31, 0xc0]); // xor r8d, r8d
+        self.assembler.bind(next);
+        self.assembler.emit(&[0x49, 0x8b, 0x51, CONTEXT_BUFFERED]); // mov rdx, [r9 + buffered]
+        self.assembler.emit(&[0x4c, 0x29, 0xc2]); // sub rdx, r8
+        self.assembler.jump_if(Condition::Equal, done);
+        self.assembler
+            .emit(&[0x4b, 0x8d, 0x74, 0x01, CONTEXT_BUFFER]); // lea rsi, [r9 + r8 + buffer]
+        self.assembler.emit(&[0xbf]); // mov edi, imm32
+        self.assembler.emit_u32(STDOUT);
+        self.system_call(SYS_WRITE);
+        self.assembler
+            .emit(&[0x48, 0x83, 0xf8, EINTR.wrapping_neg()]); // cmp rax, -EINTR
+        self.assembler.jump_if(Condition::Equal, next);
+        self.assembler.emit(&[0x48, 0x85, 0xc0]); // test rax, rax
+        self.assembler.jump_if(Condition::LessOrEqual, failed);
+        self.assembler.emit(&[0x49, 0x01, 0xc0]); // add r8, rax
+        self.assembler.jump(next);
+
+        self.assembler.bind(done);
+        self.assembler.emit(&[0x49, 0xc7, 0x41, CONTEXT_BUFFERED]); // mov qword [r9 + buffered], imm32
+        self.assembler.emit_u32(0);
+        self.assembler.emit(&[0x4c, 0x89, 0xcf]); // mov rdi, r9
+        self.assembler.emit(&[0x31, 0xc0]); // xor eax, eax
+        self.assembler.emit(&[0xc3]); // ret
+
+        // The output is dropped, so that reporting the error does not try to write it again.
+        self.assembler.bind(failed);
+        self.assembler.emit(&[0x49, 0xc7, 0x41, CONTEXT_BUFFERED]); // mov qword [r9 + buffered], imm32
+        self.assembler.emit_u32(0);
+        self.assembler.emit(&[0x4c, 0x89, 0xcf]); // mov rdi, r9
+        self.fail(&ExecutionErrorKind::OutputError.to_string());
+    }
+
+    /// Reads a byte from the standard input into `rax`, which is -1 once the input has run out
+    /// and -2 if reading failed.
+    fn read_byte(&mut self) {
+        let retry = self.assembler.new_label();
+        let end_of_input = self.assembler.new_label();
+        let failed = self.assembler.new_label();
+
+        self.assembler.bind(self.labels.read_byte);
+        self.assembler.emit(&[0x49, 0x89, 0xf9]); // mov r9, rdi
+        self.assembler.bind(retry);
+        self.assembler.emit(&[0x31, 0xff]); // xor edi, edi
+        self.assembler.emit(&[0x49, 0x8d, 0x71, CONTEXT_READ]); // lea rsi, [r9 + read]
+        self.assembler.emit(&[0xba]); // mov edx, imm32
+        self.assembler.emit_u32(1);
+        self.system_call(SYS_READ);
+        self.assembler
+            .emit(&[0x48, 0x83, 0xf8, EINTR.wrapping_neg()]); // cmp rax, -EINTR
+        self.assembler.jump_if(Condition::Equal, retry);
+        self.assembler.emit(&[0x4c, 0x89, 0xcf]); // mov rdi, r9
+        self.assembler.emit(&[0x48, 0x85, 0xc0]); // test rax, rax
+        self.assembler.jump_if(Condition::Sign, failed);
+        self.assembler.jump_if(Condition::Equal, end_of_input);
+        self.assembler.emit(&[0x0f, 0xb6, 0x47, CONTEXT_READ]); // movzx eax, byte [rdi + read]
+        self.assembler.emit(&[0xc3]); // ret
+
+        self.assembler.bind(end_of_input);
+        self.assembler.emit(&[0x48, 0xc7, 0xc0]); // mov rax, imm32
+        self.assembler.emit_u32(-1i32 as u32);
+        self.assembler.emit(&[0xc3]); // ret
+
+        self.assembler.bind(failed);
+        self.set_message(&ExecutionErrorKind::InputError.to_string());
+        self.assembler.emit(&[0x48, 0xc7, 0xc0]); // mov rax, imm32
+        self.assembler.emit_u32(-2i32 as u32);
+        self.assembler.emit(&[0xc3]); // ret
+    }
+
+    /// Stores the message in the context of `rdi` and returns 1.
+    fn fail(&mut self, message: &str) {
+        self.set_message(message);
+        self.assembler.emit(&[0xb8]); // mov eax, imm32
+        self.assembler.emit_u32(1);
+        self.assembler.emit(&[0xc3]); // ret
+    }
+
+    /// Stores the message, followed by a newline, in the context of `rdi`.
+    fn set_message(&mut self, message: &str) {
+        let label = self.assembler.new_label();
+        let message = format!("{message}\n");
+
+        self.assembler.emit(&[0x48, 0x8d, 0x05]); // lea rax, [rip + message]
+        self.assembler.emit_relative(label);
+        self.assembler.emit(&[0x48, 0x89, 0x47, CONTEXT_MESSAGE]); // mov [rdi + message], rax
+        self.assembler
+            .emit(&[0x48, 0xc7, 0x47, CONTEXT_MESSAGE_LENGTH]); // mov qword [rdi + length], imm32
+        self.assembler.emit_u32(message.len() as u32);
+        self.labels.messages.push((label, message));
+    }
+
+    /// Makes the system call with the number, clobbering `rcx` and `r11`.
+    fn system_call(&mut self, number: u32) {
+        self.assembler.emit(&[0xb8]); // mov eax, imm32
+        self.assembler.emit_u32(number);
+        self.assembler.emit(&[0x0f, 0x05]); // syscall
+    }
+}
@@ -0,0 +1,56 @@
+use std::ptr;
+
+use super::jit_error::JitError;
+
+/// A block of memory holding machine code that can be executed but no longer written to.
+pub struct ExecutableMemory {
+    pointer: *mut libc::c_void,
+    length: usize,
+}
+
+impl ExecutableMemory {
+    pub fn new(code: &[u8]) -> Result<ExecutableMemory, JitError> {
+        let length = code.len().max(1);
+
+        // SAFETY: mapping fresh anonymous memory does not touch any existing memory.
+        let pointer = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                length,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        if pointer == libc::MAP_FAILED {
+            return Err(JitError::MemoryError);
+        }
+
+        let memory = ExecutableMemory { pointer, length };
+
+        // SAFETY: the mapping is writable and at least `code.len()` bytes long, and it is only
+        // made executable after the code has been copied into it.
+        unsafe {
+            ptr::copy_nonoverlapping(code.as_ptr(), pointer as *mut u8, code.len());
+            if libc::mprotect(pointer, length, libc::PROT_READ | libc::PROT_EXEC) != 0 {
+                return Err(JitError::MemoryError);
+            }
+        }
+
+        Ok(memory)
+    }
+
+    pub fn as_ptr(&self) -> *const u8 {
+        self.pointer as *const u8
+    }
+}
+
+impl Drop for ExecutableMemory {
+    fn drop(&mut self) {
+        // SAFETY: the mapping was created in `new` and nothing refers to it anymore.
+        unsafe {
+            libc::munmap(self.pointer, self.length);
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use derive_more::Display;
+
+use crate::executor::{cell_type::CellType, overflow_policy::OverflowPolicy, tape::TapeMode};
+
+#[derive(Debug, Display)]
+pub enum JitError {
+    #[display("JIT Error: Cells of type {} can not be compiled!", _0)]
+    UnsupportedCellType(CellType),
+    #[display(
+        "JIT Error: Only wrapping cells can be compiled, not the {} overflow policy!",
+        _0
+    )]
+    UnsupportedOverflowPolicy(OverflowPolicy),
+    #[display("JIT Error: A {} tape can not be compiled!", _0)]
+    UnsupportedTapeMode(TapeMode),
+    #[display("JIT Error: Step limits and timeouts can not be compiled!")]
+    UnsupportedLimits,
+    #[display("JIT Error: The data pointer moves too far at once!")]
+    OffsetTooLarge,
+    #[display("JIT Error: The program has too many commands!")]
+    ProgramTooLarge,
+    #[display("JIT Error: Unable to allocate executable memory!")]
+    MemoryError,
+    #[display("JIT Error: Compiling is only supported on x86-64 Linux!")]
+    UnsupportedPlatform,
+}
@@ -0,0 +1,238 @@
+use std::io::{self, Read, Write};
+
+use crate::executor::{
+    executing_error::ExecutionError,
+    execution_error_kind::ExecutionErrorKind,
+    executor_command::ExecutorCommand,
+    executor_config::ExecutorConfig,
+    executor_state::{ExecutorState, Int},
+    optimized_command::{OptimizedCommand, optimize_with_spans},
+    source_span::SourceSpan,
+    tape::TapeMode,
+};
+
+use super::{
+    compiler::{EXIT_CALLBACK_FAILED, EXIT_FINISHED, EXIT_TOO_FAR_LEFT, compile},
+    executable_memory::ExecutableMemory,
+    jit_error::JitError,
+};
+
+/// A brainfuck program compiled into x86-64 machine code. It behaves exactly like the program run
+/// by `ExecutorState` with the same configuration, and fails at the same command.
+pub struct JitProgram {
+    memory: ExecutableMemory,
+    config: ExecutorConfig,
+    commands: Vec<ExecutorCommand>, // the program as written, to find out where it failed
+    optimized: Vec<OptimizedCommand>,
+    spans: Vec<SourceSpan>,
+}
+
+/// The context the compiled code runs with. The layout of the first fields is fixed, see
+/// `compiler`.
+#[repr(C)]
+struct Context<R, W> {
+    tape: *mut u8,
+    length: u64,
+    output: extern "C" fn(*mut Context<R, W>, i64) -> u64,
+    input: extern "C" fn(*mut Context<R, W>, i64, *mut i64) -> u64,
+    grow: extern "C" fn(*mut Context<R, W>, u64) -> u64,
+    failed_command: u64,
+    failed_pointer: u64,
+    cells: Vec<u8>,
+    cell_size: usize,
+    // Performs the I/O on its only cell, so it behaves exactly like the interpreter.
+    io: ExecutorState<R, W>,
+    error: Option<ExecutionErrorKind>,
+}
+
+impl JitProgram {
+    pub fn compile(
+        commands: &[ExecutorCommand],
+        config: ExecutorConfig,
+    ) -> Result<JitProgram, JitError> {
+        let (optimized, spans) = optimize_with_spans(commands, &config);
+        let code = compile(&optimized, &config)?;
+
+        Ok(JitProgram {
+            memory: ExecutableMemory::new(&code)?,
+            config,
+            commands: commands.to_vec(),
+            optimized,
+            spans,
+        })
+    }
+
+    /// Runs the compiled program to its end, reading from `input` and writing to `output`.
+    pub fn run<R: Read, W: Write>(&self, input: R, output: W) -> Result<(), ExecutionError> {
+        self.config.check_tape()?;
+
+        let cell_size = self.config.cell_type.size();
+        let length = match self.config.tape_mode {
+            TapeMode::Fixed | TapeMode::Circular => self.config.tape_length.max(1),
+            _ => 1,
+        };
+
+        let mut context = Context {
+            tape: std::ptr::null_mut(),
+            length: length as u64,
+            output: output_callback::<R, W>,
+            input: input_callback::<R, W>,
+            grow: grow_callback::<R, W>,
+            failed_command: 0,
+            failed_pointer: 0,
+            cells: vec![0; length * cell_size],
+            cell_size,
+            io: ExecutorState::with_io(
+                Vec::new(),
+                ExecutorConfig {
+                    tape_mode: TapeMode::Growable,
+                    ..self.config
+                },
+                input,
+                output,
+            ),
+            error: None,
+        };
+        context.tape = context.cells.as_mut_ptr();
+
+        // SAFETY: the memory holds a function compiled by `compile`, which only accesses the
+        // context through the fields whose layout is fixed and keeps the data pointer on the tape.
+        let exit_code = unsafe {
+            let function: extern "C" fn(*mut Context<R, W>) -> u64 =
+                std::mem::transmute(self.memory.as_ptr());
+            function(&mut context)
+        };
+
+        let kind = match exit_code {
+            EXIT_FINISHED => return context.io.flush(),
+            EXIT_CALLBACK_FAILED => context
+                .error
+                .take()
+                .unwrap_or(ExecutionErrorKind::InputError),
+            EXIT_TOO_FAR_LEFT => ExecutionErrorKind::TooFarLeft,
+            _ => ExecutionErrorKind::TooFarRight,
+        };
+        Err(self.locate_error(kind, &context))
+    }
+
+    /// Finds out where the program failed. The data never changes halfway through an optimized
+    /// command that fails, so running the command as written from there fails at the same
+    /// command as `ExecutorState` would.
+    fn locate_error<R, W>(
+        &self,
+        kind: ExecutionErrorKind,
+        context: &Context<R, W>,
+    ) -> ExecutionError {
+        let index = context.failed_command as usize;
+        let span = self.spans[index];
+        let pointer = context.failed_pointer as isize;
+
+        let mut state = self.replay_state(context);
+        let (from, pointer) = match &self.optimized[index] {
+            OptimizedCommand::Move(offset) => (span.start, pointer - offset),
+            OptimizedCommand::Scan(offset) => (span.origin + 1, pointer - offset),
+            OptimizedCommand::Multiply(_) => (span.start, pointer),
+            // Only the I/O can fail otherwise, which the callbacks did exactly like the
+            // interpreter.
+            _ => {
+                state.set_pointer(pointer);
+                return state.error_at(kind, span.origin);
+            }
+        };
+
+        state.set_pointer(pointer);
+        state.set_pc(from);
+        while state.get_pc() < span.end {
+            if let Err(err) = state.execute_once() {
+                return err;
+            }
+        }
+        state.error_at(kind, span.origin)
+    }
+
+    /// An `ExecutorState` holding the program as written and the tape the compiled code left
+    /// behind, without any I/O or limits on the steps.
+    fn replay_state<R, W>(&self, context: &Context<R, W>) -> ExecutorState<io::Empty, io::Sink> {
+        let mut state = ExecutorState::with_io(
+            self.commands.clone(),
+            ExecutorConfig {
+                max_steps: None,
+                timeout: None,
+                ..self.config
+            },
+            io::empty(),
+            io::sink(),
+        );
+
+        let length = context.cells.len() / context.cell_size;
+        if self.config.tape_mode == TapeMode::Growable {
+            // The compiled code already grew the tape this far, so this can not fail.
+            let _ = state.move_pointer(length as isize - 1, &mut ());
+        }
+        for (index, cell) in context.cells.chunks(context.cell_size).enumerate() {
+            let mut bytes = [0; 16];
+            bytes[..cell.len()].copy_from_slice(cell);
+            let value = self
+                .config
+                .cell_type
+                .wrap(u128::from_le_bytes(bytes) as Int);
+            state.set_cell_value(index as isize, value);
+        }
+
+        state
+    }
+}
+
+extern "C" fn output_callback<R: Read, W: Write>(context: *mut Context<R, W>, value: i64) -> u64 {
+    // SAFETY: the compiled code passes back the context it was called with.
+    let context = unsafe { &mut *context };
+
+    context.io.set_current_cell(value as Int, &mut ());
+    match context.io.output(&mut ()) {
+        Ok(_) => 0,
+        Err(err) => {
+            context.error = Some(err);
+            1
+        }
+    }
+}
+
+extern "C" fn input_callback<R: Read, W: Write>(
+    context: *mut Context<R, W>,
+    value: i64,
+    result: *mut i64,
+) -> u64 {
+    // SAFETY: the compiled code passes back the context it was called with and a pointer to a
+    // slot on its stack.
+    let (context, result) = unsafe { (&mut *context, &mut *result) };
+
+    context.io.set_current_cell(value as Int, &mut ());
+    match context.io.input(&mut ()) {
+        Ok(_) => {
+            *result = context.io.current_cell() as i64;
+            0
+        }
+        Err(err) => {
+            context.error = Some(err);
+            1
+        }
+    }
+}
+
+extern "C" fn grow_callback<R: Read, W: Write>(context: *mut Context<R, W>, index: u64) -> u64 {
+    // SAFETY: the compiled code passes back the context it was called with.
+    let context = unsafe { &mut *context };
+
+    let length = index as usize + 1;
+    if let Some(max_cells) = context.io.get_config().max_cells
+        && length > max_cells
+    {
+        context.error = Some(ExecutionErrorKind::TapeLimitExceeded { cells: max_cells });
+        return 1;
+    }
+    context.cells.resize(length * context.cell_size, 0);
+    context.tape = context.cells.as_mut_ptr();
+    context.length = length as u64;
+
+    0
+}
@@ -0,0 +1,8 @@
+pub mod assembler;
+pub mod compiler;
+pub mod elf_executable;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub mod executable_memory;
+pub mod jit_error;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub mod jit_program;
@@ -1,4 +1,12 @@
 pub mod debugger;
 pub mod executor;
+pub mod jit;
+pub mod parse_error;
 pub mod parsing_src;
+pub mod profiler;
+pub mod source_position;
 pub mod start;
+pub mod trace;
+pub mod transpiler;
+pub mod unmatched_bracket;
+pub mod wasm;
@@ -1,21 +1,106 @@
 pub mod command_line_args;
 
+use std::process::ExitCode;
+
 use clap::Parser;
 
-use bfdbg::start::{debug, execute};
-use command_line_args::Args;
+use bfdbg::{
+    executor::executor_config::ExecutorConfig,
+    start::{
+        Backend, build, checkpoint, compile, coverage, debug, execute, profile, report, resume,
+        trace,
+    },
+};
+use command_line_args::{Args, Command};
 
-fn main() {
+fn main() -> ExitCode {
     let args = Args::parse();
 
-    let cmd_string = match std::fs::read_to_string(args.path) {
-        Ok(string) => string,
-        Err(_) => panic!("Failed to read from the file!"),
+    if let Some(path) = &args.resume {
+        return resume(
+            path,
+            args.max_steps,
+            args.timeout,
+            args.max_cells,
+            args.snapshot.as_deref(),
+        );
+    }
+
+    let path = match &args.command {
+        Some(
+            Command::Compile { path, .. }
+            | Command::Build { path, .. }
+            | Command::Coverage { path, .. },
+        ) => path,
+        None => args.path.as_deref().unwrap_or_default(),
     };
+    let Ok(source) = std::fs::read_to_string(path) else {
+        return report(format!(
+            "IO Error: Failed to read the source file {}!",
+            path
+        ));
+    };
+
+    match &args.command {
+        Some(Command::Compile {
+            target,
+            output,
+            config,
+            ..
+        }) => {
+            return compile(
+                &source,
+                config.executor_config(),
+                *target,
+                output.as_deref(),
+            );
+        }
+        Some(Command::Build { output, config, .. }) => {
+            return build(&source, config.executor_config(), output);
+        }
+        Some(Command::Coverage {
+            path,
+            inputs,
+            lcov,
+            config,
+        }) => {
+            return coverage(
+                &source,
+                config.executor_config(),
+                path,
+                inputs,
+                lcov.as_deref(),
+            );
+        }
+        None => {}
+    }
+
+    let config = ExecutorConfig {
+        max_steps: args.max_steps,
+        timeout: args.timeout,
+        max_cells: args.max_cells,
+        ..args.config.executor_config()
+    };
+    if let Err(info) = config.check_tape() {
+        return report(info);
+    }
 
     if args.debug {
-        debug(&cmd_string);
+        debug(&source, config)
+    } else if args.profile {
+        profile(&source, config)
+    } else if let Some(path) = &args.trace {
+        trace(&source, config, path)
+    } else if let Some(path) = &args.snapshot {
+        checkpoint(&source, config, path)
     } else {
-        execute(&cmd_string);
+        let backend = if args.jit {
+            Backend::Jit
+        } else if args.no_optimize {
+            Backend::Interpreter
+        } else {
+            Backend::OptimizedInterpreter
+        };
+        execute(&source, config, backend)
     }
 }
@@ -0,0 +1,22 @@
+use std::{error::Error, fmt};
+
+use crate::unmatched_bracket::UnmatchedBracket;
+
+/// The error a program fails to parse with, listing every bracket without a match in the order
+/// they appear in the source code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub unmatched: Vec<UnmatchedBracket>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Syntax Error: '['s and ']'s do not properly match.")?;
+        for bracket in &self.unmatched {
+            write!(f, "\n{}", bracket)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
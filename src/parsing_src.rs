@@ -1,57 +1,210 @@
 use std::collections::HashSet;
 
-use crate::executor::executor_command::ExecutorCommand as Command;
+use crate::{
+    executor::executor_command::ExecutorCommand as Command, parse_error::ParseError,
+    source_position::SourcePosition, unmatched_bracket::UnmatchedBracket,
+};
 
+/// Every character that is a command, with the command it stands for. The targets of the jumps
+/// are filled in once the matching bracket is found.
+const COMMANDS: [(char, Command); 8] = [
+    ('>', Command::MoveRight),
+    ('<', Command::MoveLeft),
+    ('+', Command::Increment),
+    ('-', Command::Decrement),
+    ('.', Command::Output),
+    (',', Command::Input),
+    ('[', Command::JumpForward(0)),
+    (']', Command::JumpBack(0)),
+];
+
+/// Translates the source code into commands, together with the indices of the commands that are
+/// breakpoints if `debug` is set. Fails with every bracket that has no matching bracket.
 pub fn translate_into_commands(
     string: &str,
     debug: bool,
-) -> Result<(Vec<Command>, HashSet<usize>), String> {
+) -> Result<(Vec<Command>, HashSet<usize>), ParseError> {
     let mut commands: Vec<Command> = Vec::new();
-    let mut pos_in_commands: Vec<usize> = Vec::new();
+    let mut pos_in_commands: Vec<(usize, SourcePosition)> = Vec::new();
+    let mut unopened: Vec<SourcePosition> = Vec::new();
 
     let mut breakpoints = HashSet::new();
 
-    let mut current_cmd_ptr: usize = 0;
-
-    for current_char in string.chars() {
-        let current_cmd = match current_char {
-            '>' => Command::MoveRight,
-            '<' => Command::MoveLeft,
-            '+' => Command::Increment,
-            '-' => Command::Decrement,
-            '.' => Command::Output,
-            ',' => Command::Input,
-            '[' => {
-                pos_in_commands.push(current_cmd_ptr);
-                Command::JumpForward(0)
-            }
-            ']' => {
-                if let Some(pos) = pos_in_commands.pop() {
-                    commands[pos] = Command::JumpForward(current_cmd_ptr);
-                    Command::JumpBack(pos)
+    for (current_char, position) in positioned_chars(string) {
+        if current_char == ':' && debug {
+            breakpoints.insert(commands.len());
+            continue;
+        }
+        let Some(mut current_cmd) = command_for(current_char) else {
+            continue;
+        };
+
+        match current_cmd {
+            Command::JumpForward(_) => pos_in_commands.push((commands.len(), position)),
+            Command::JumpBack(_) => {
+                if let Some((pos, _)) = pos_in_commands.pop() {
+                    commands[pos] = Command::JumpForward(commands.len());
+                    current_cmd = Command::JumpBack(pos);
                 } else {
-                    return Err(String::from(
-                        "Syntax Error: '['s and ']'s do not properly match. There are more ']'s than '['s",
-                    ));
+                    // Keeps going to find the other brackets without a match as well.
+                    unopened.push(position);
                 }
             }
-            ':' if debug => {
-                breakpoints.insert(current_cmd_ptr);
-                continue;
-            }
-            _ => {
-                continue;
-            }
-        };
-        current_cmd_ptr += 1;
+            _ => {}
+        }
         commands.push(current_cmd);
     }
 
-    if !pos_in_commands.is_empty() {
-        Err(String::from(
-            "Syntax Error: '['s and ']'s do not properly match. There are more '['s than ']'s",
-        ))
-    } else {
-        Ok((commands, breakpoints))
+    if pos_in_commands.is_empty() && unopened.is_empty() {
+        return Ok((commands, breakpoints));
+    }
+
+    let lines: Vec<&str> = string.lines().collect();
+    let unclosed = pos_in_commands
+        .into_iter()
+        .map(|(_, position)| ('[', position));
+    let mut unmatched: Vec<UnmatchedBracket> = unopened
+        .into_iter()
+        .map(|position| (']', position))
+        .chain(unclosed)
+        .map(|(bracket, position)| UnmatchedBracket {
+            bracket,
+            position,
+            line: lines
+                .get(position.line - 1)
+                .copied()
+                .unwrap_or_default()
+                .to_string(),
+        })
+        .collect();
+    unmatched.sort_by_key(|bracket| bracket.position);
+
+    Err(ParseError { unmatched })
+}
+
+/// Finds where every command of the program is in the source code, so that the positions line up
+/// with the commands given by `translate_into_commands`.
+pub fn source_positions(string: &str) -> Vec<SourcePosition> {
+    positioned_chars(string)
+        .filter(|(current_char, _)| command_for(*current_char).is_some())
+        .map(|(_, position)| position)
+        .collect()
+}
+
+fn command_for(current_char: char) -> Option<Command> {
+    COMMANDS
+        .iter()
+        .find(|(command_char, _)| *command_char == current_char)
+        .map(|(_, command)| command.clone())
+}
+
+/// Goes through every character of the source code together with its position.
+fn positioned_chars(string: &str) -> impl Iterator<Item = (char, SourcePosition)> + '_ {
+    string.lines().enumerate().flat_map(|(line, text)| {
+        text.chars().enumerate().map(move |(column, current_char)| {
+            let position = SourcePosition {
+                line: line + 1,
+                column: column + 1,
+            };
+            (current_char, position)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::executor_command::ExecutorCommand as Command, source_position::SourcePosition,
+    };
+
+    use super::{source_positions, translate_into_commands};
+
+    fn position(line: usize, column: usize) -> SourcePosition {
+        SourcePosition { line, column }
+    }
+
+    #[test]
+    fn every_unmatched_bracket_is_listed() {
+        let source = "]]+[\n[-]]\n>[[\n]<";
+        let err = translate_into_commands(source, false).unwrap_err();
+
+        let unmatched: Vec<_> = err
+            .unmatched
+            .iter()
+            .map(|bracket| (bracket.bracket, bracket.position, bracket.line.as_str()))
+            .collect();
+        assert_eq!(
+            unmatched,
+            [
+                (']', position(1, 1), "]]+["),
+                (']', position(1, 2), "]]+["),
+                ('[', position(3, 2), ">[["),
+            ]
+        );
+
+        let err = translate_into_commands("[[]\n+[", false).unwrap_err();
+        let unmatched: Vec<_> = err
+            .unmatched
+            .iter()
+            .map(|bracket| (bracket.bracket, bracket.position))
+            .collect();
+        assert_eq!(unmatched, [('[', position(1, 1)), ('[', position(2, 2))]);
+    }
+
+    #[test]
+    fn positions_count_lines_and_characters() {
+        let source = "+ -\n\nhé >x<\r\n\t.:,";
+
+        assert_eq!(
+            source_positions(source),
+            [
+                position(1, 1),
+                position(1, 3),
+                position(3, 4),
+                position(3, 6),
+                position(4, 2),
+                position(4, 4),
+            ]
+        );
+
+        // Breakpoints are not commands, so the positions still line up with the commands.
+        let (commands, breakpoints) = translate_into_commands(source, true).unwrap();
+        assert_eq!(commands.len(), source_positions(source).len());
+        assert!(breakpoints.contains(&5));
+    }
+
+    #[test]
+    fn jumps_lead_to_their_matching_bracket() {
+        let (commands, _) = translate_into_commands("[>[-]<]", false).unwrap();
+
+        assert_eq!(
+            commands,
+            [
+                Command::JumpForward(6),
+                Command::MoveRight,
+                Command::JumpForward(4),
+                Command::Decrement,
+                Command::JumpBack(2),
+                Command::MoveLeft,
+                Command::JumpBack(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn carets_line_up_with_the_bracket() {
+        let err = translate_into_commands("+\n\n\n\n\n\n\n\n\nab\t é[", false).unwrap_err();
+
+        assert_eq!(
+            err.unmatched[0].to_string(),
+            "The '[' at line 10, column 6 is never closed:\n10 | ab\t é[\n   |   \t  ^"
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Syntax Error: '['s and ']'s do not properly match.\n{}",
+                err.unmatched[0]
+            )
+        );
     }
 }
@@ -0,0 +1,269 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{Read, Write},
+};
+
+use crate::{
+    executor::{
+        executing_error::ExecutionError, executing_state::ExecutionState,
+        executor_state::ExecutorState,
+    },
+    source_position::SourcePosition,
+};
+
+/// Records which commands of a program ran, adding up the counts over any number of runs.
+pub struct Coverage {
+    positions: Vec<SourcePosition>,
+    hits: Vec<u64>,
+    runs: usize,
+}
+
+impl Coverage {
+    /// Creates an empty coverage of a program, where `positions` are the places of its commands
+    /// in the source code as given by `source_positions`.
+    pub fn new(positions: Vec<SourcePosition>) -> Coverage {
+        Coverage {
+            hits: vec![0; positions.len()],
+            positions,
+            runs: 0,
+        }
+    }
+
+    /// Runs the program to its end, counting every command that runs. The program counts as run
+    /// even if it fails, and the commands before the failure count as covered.
+    pub fn record_run<R: Read, W: Write>(
+        &mut self,
+        state: &mut ExecutorState<R, W>,
+    ) -> Result<(), ExecutionError> {
+        self.runs += 1;
+
+        loop {
+            let (pc, steps) = (state.get_pc(), state.get_steps());
+            let result = state.execute_once();
+            // A command stopped by a limit has not run.
+            if state.get_steps() > steps {
+                self.record(pc);
+            }
+
+            if let ExecutionState::Finished = result? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Counts a run of the command at the index. Indices past the end of the program are ignored.
+    pub fn record(&mut self, pc: usize) {
+        if let Some(hits) = self.hits.get_mut(pc) {
+            *hits += 1;
+        }
+    }
+
+    /// The number of times every command ran, by the index of the command.
+    pub fn get_hits(&self) -> &[u64] {
+        &self.hits
+    }
+
+    /// The number of runs recorded by `record_run`.
+    pub fn get_runs(&self) -> usize {
+        self.runs
+    }
+
+    /// The indices of the commands that never ran.
+    pub fn uncovered(&self) -> Vec<usize> {
+        (0..self.hits.len())
+            .filter(|index| self.hits[*index] == 0)
+            .collect()
+    }
+
+    /// Writes the coverage in the LCOV format, for the source file at `source_path`. A line
+    /// counts as many hits as the command on it that ran the most.
+    pub fn to_lcov(&self, source_path: &str) -> String {
+        let lines = self.line_hits();
+        let mut lcov = String::new();
+
+        let _ = writeln!(lcov, "TN:");
+        let _ = writeln!(lcov, "SF:{source_path}");
+        for (line, hits) in &lines {
+            let _ = writeln!(lcov, "DA:{line},{hits}");
+        }
+        let _ = writeln!(lcov, "LF:{}", lines.len());
+        let _ = writeln!(
+            lcov,
+            "LH:{}",
+            lines.values().filter(|hits| **hits > 0).count()
+        );
+        let _ = writeln!(lcov, "end_of_record");
+
+        lcov
+    }
+
+    /// Writes the source code with the number of hits in front of every line holding commands,
+    /// `#####` if none of them ran, and a line of `^` under the commands that never ran.
+    pub fn annotate(&self, source: &str) -> String {
+        let lines = self.line_hits();
+        let mut uncovered: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for index in self.uncovered() {
+            if let Some(position) = self.positions.get(index) {
+                uncovered
+                    .entry(position.line)
+                    .or_default()
+                    .push(position.column);
+            }
+        }
+
+        let covered = self.hits.len() - self.uncovered().len();
+        let mut report = String::new();
+        let _ = writeln!(
+            report,
+            "Coverage: {covered} of {} commands ran in {} run{}.",
+            self.hits.len(),
+            self.runs,
+            if self.runs == 1 { "" } else { "s" }
+        );
+
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+            let count = match lines.get(&line) {
+                Some(0) => String::from("#####"),
+                Some(hits) => hits.to_string(),
+                None => String::from("-"),
+            };
+            let _ = writeln!(report, "{count:>9} | {text}");
+
+            if let Some(columns) = uncovered.get(&line) {
+                // Tabs are kept, so that the markers line up with the commands.
+                let markers: String = text
+                    .chars()
+                    .enumerate()
+                    .map(|(column, current_char)| {
+                        if columns.contains(&(column + 1)) {
+                            '^'
+                        } else if current_char == '\t' {
+                            '\t'
+                        } else {
+                            ' '
+                        }
+                    })
+                    .collect();
+                let _ = writeln!(report, "{:>9} | {}", "", markers.trim_end());
+            }
+        }
+
+        report
+    }
+
+    /// The hits of every line holding commands, which are the hits of its most run command.
+    fn line_hits(&self) -> BTreeMap<usize, u64> {
+        let mut lines: BTreeMap<usize, u64> = BTreeMap::new();
+
+        for (position, hits) in self.positions.iter().zip(&self.hits) {
+            let line = lines.entry(position.line).or_default();
+            *line = (*line).max(*hits);
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::{
+            eof_policy::EofPolicy, execution_error_kind::ExecutionErrorKind,
+            executor_config::ExecutorConfig, executor_state::ExecutorState,
+        },
+        parsing_src::{source_positions, translate_into_commands},
+    };
+
+    use super::Coverage;
+
+    const SOURCE: &str = ",[\n\t->+<]\nnotes\n.";
+
+    fn coverage(source: &str, inputs: &[&[u8]]) -> Coverage {
+        let (commands, _) = translate_into_commands(source, false).unwrap();
+        let config = ExecutorConfig {
+            eof_policy: EofPolicy::Zero,
+            ..ExecutorConfig::default()
+        };
+        let mut coverage = Coverage::new(source_positions(source));
+
+        for input in inputs {
+            let mut state = ExecutorState::with_io(commands.clone(), config, *input, Vec::new());
+            coverage.record_run(&mut state).unwrap();
+        }
+        coverage
+    }
+
+    #[test]
+    fn hits_add_up_over_the_runs() {
+        let once = coverage(SOURCE, &[b""]);
+        assert_eq!(once.get_hits(), [1, 1, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(once.uncovered(), [2, 3, 4, 5, 6]);
+
+        let twice = coverage(SOURCE, &[b"", b"\x01"]);
+        assert_eq!(twice.get_hits(), [2, 2, 1, 1, 1, 1, 1, 2]);
+        assert_eq!(twice.get_runs(), 2);
+        assert!(twice.uncovered().is_empty());
+    }
+
+    #[test]
+    fn failed_runs_count_as_runs() {
+        let (commands, _) = translate_into_commands("+<+", false).unwrap();
+        let mut coverage = Coverage::new(source_positions("+<+"));
+        let mut state =
+            ExecutorState::with_io(commands, ExecutorConfig::default(), &b""[..], Vec::new());
+
+        let err = coverage.record_run(&mut state).unwrap_err();
+        assert_eq!(err.get_kind(), ExecutionErrorKind::TooFarLeft);
+        assert_eq!(coverage.get_runs(), 1);
+        assert_eq!(coverage.get_hits()[0], 1);
+        assert_eq!(coverage.get_hits()[2], 0);
+    }
+
+    #[test]
+    fn lcov_has_the_hits_of_every_line_with_commands() {
+        assert_eq!(
+            coverage(SOURCE, &[b""]).to_lcov("program.bf"),
+            "TN:\nSF:program.bf\nDA:1,1\nDA:2,0\nDA:4,1\nLF:3\nLH:2\nend_of_record\n"
+        );
+        assert_eq!(
+            coverage(SOURCE, &[b"", b"\x01"]).to_lcov("program.bf"),
+            "TN:\nSF:program.bf\nDA:1,2\nDA:2,1\nDA:4,2\nLF:3\nLH:3\nend_of_record\n"
+        );
+    }
+
+    #[test]
+    fn annotated_sources_mark_the_commands_that_never_ran() {
+        assert_eq!(
+            coverage(SOURCE, &[b""]).annotate(SOURCE),
+            "Coverage: 3 of 8 commands ran in 1 run.\n\
+             \x20       1 | ,[\n\
+             \x20   ##### | \t->+<]\n\
+             \x20         | \t^^^^^\n\
+             \x20       - | notes\n\
+             \x20       1 | .\n"
+        );
+        assert_eq!(
+            coverage(SOURCE, &[b"", b"\x01"]).annotate(SOURCE),
+            "Coverage: 8 of 8 commands ran in 2 runs.\n\
+             \x20       2 | ,[\n\
+             \x20       1 | \t->+<]\n\
+             \x20       - | notes\n\
+             \x20       2 | .\n"
+        );
+    }
+
+    #[test]
+    fn partly_covered_lines_mark_only_the_commands_that_never_ran() {
+        let source = "[-]>.";
+        let annotated = coverage(source, &[b""]).annotate(source);
+
+        assert_eq!(
+            annotated,
+            "Coverage: 3 of 5 commands ran in 1 run.\n\
+             \x20       1 | [-]>.\n\
+             \x20         |  ^^\n"
+        );
+    }
+}
@@ -0,0 +1,14 @@
+/// How often a loop ran while the program was being profiled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoopProfile {
+    /// The index of the `[` command.
+    pub start: usize,
+    /// The index of the matching `]` command.
+    pub end: usize,
+    /// The number of times the program reached the loop.
+    pub entries: u64,
+    /// The number of times the body of the loop ran to its end.
+    pub iterations: u64,
+    /// The number of steps spent on the commands of the loop, including its nested loops.
+    pub steps: u64,
+}
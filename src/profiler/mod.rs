@@ -0,0 +1,3 @@
+pub mod coverage;
+pub mod loop_profile;
+pub mod profile;
@@ -0,0 +1,210 @@
+use std::fmt::Write;
+
+use crate::{executor::executor_command::ExecutorCommand, source_position::SourcePosition};
+
+use super::loop_profile::LoopProfile;
+
+/// Counts how many times every command of a program ran, to find out where the program spends
+/// its time.
+pub struct Profile {
+    commands: Vec<ExecutorCommand>,
+    positions: Vec<SourcePosition>,
+    counts: Vec<u64>,
+}
+
+impl Profile {
+    /// Creates an empty profile of the program, where `positions` are the places of the commands
+    /// in the source code as given by `source_positions`.
+    pub fn new(commands: &[ExecutorCommand], positions: Vec<SourcePosition>) -> Profile {
+        Profile {
+            commands: commands.to_vec(),
+            positions,
+            counts: vec![0; commands.len()],
+        }
+    }
+
+    /// Counts a run of the command at the index. Indices past the end of the program are ignored.
+    pub fn record(&mut self, pc: usize) {
+        if let Some(count) = self.counts.get_mut(pc) {
+            *count += 1;
+        }
+    }
+
+    /// The number of times every command ran, by the index of the command.
+    pub fn get_counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// The total number of steps the program ran for.
+    pub fn total_steps(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// How often every loop ran, in the order the loops start in.
+    pub fn loops(&self) -> Vec<LoopProfile> {
+        self.commands
+            .iter()
+            .enumerate()
+            .filter_map(|(start, command)| match command {
+                ExecutorCommand::JumpForward(end) => Some(LoopProfile {
+                    start,
+                    end: *end,
+                    entries: self.counts[start],
+                    iterations: self.counts[*end],
+                    steps: self.counts[start..=*end].iter().sum(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Writes a report of the loops with the most iterations and the commands that ran the most,
+    /// at most `limit` of each, located by their line and column in the source code.
+    pub fn report(&self, limit: usize) -> String {
+        let total = self.total_steps();
+        let mut report = String::new();
+        let _ = writeln!(report, "Profile: the program ran for {total} steps.");
+
+        let mut loops: Vec<LoopProfile> = self
+            .loops()
+            .into_iter()
+            .filter(|profile| profile.entries > 0)
+            .collect();
+        loops.sort_by(|a, b| b.iterations.cmp(&a.iterations).then(a.start.cmp(&b.start)));
+        if !loops.is_empty() {
+            let _ = writeln!(report, "Hottest loops:");
+        }
+        for profile in loops.iter().take(limit) {
+            let _ = writeln!(
+                report,
+                "    {} to {}: {} iterations, entered {} times, {} steps ({})",
+                self.position(profile.start),
+                self.position(profile.end),
+                profile.iterations,
+                profile.entries,
+                profile.steps,
+                percentage(profile.steps, total)
+            );
+        }
+
+        let mut commands: Vec<usize> = (0..self.counts.len())
+            .filter(|index| self.counts[*index] > 0)
+            .collect();
+        commands.sort_by(|a, b| self.counts[*b].cmp(&self.counts[*a]).then(a.cmp(b)));
+        if !commands.is_empty() {
+            let _ = writeln!(report, "Hottest commands:");
+        }
+        for index in commands.into_iter().take(limit) {
+            let _ = writeln!(
+                report,
+                "    {} {}: {} times ({})",
+                self.position(index),
+                self.commands[index],
+                self.counts[index],
+                percentage(self.counts[index], total)
+            );
+        }
+
+        report
+    }
+
+    fn position(&self, index: usize) -> String {
+        match self.positions.get(index) {
+            Some(position) => position.to_string(),
+            None => format!("command {index}"),
+        }
+    }
+}
+
+fn percentage(part: u64, total: u64) -> String {
+    format!("{:.1}%", part as f64 * 100.0 / total.max(1) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::{
+            executing_state::ExecutionState, executor_config::ExecutorConfig,
+            executor_state::ExecutorState,
+        },
+        parsing_src::{source_positions, translate_into_commands},
+        profiler::loop_profile::LoopProfile,
+    };
+
+    use super::Profile;
+
+    fn profile(source: &str) -> Profile {
+        let (commands, _) = translate_into_commands(source, false).unwrap();
+        let mut profile = Profile::new(&commands, source_positions(source));
+        let mut state =
+            ExecutorState::with_io(commands, ExecutorConfig::default(), &b""[..], Vec::new());
+
+        loop {
+            let pc = state.get_pc();
+            if let ExecutionState::Finished = state.execute_once().unwrap() {
+                return profile;
+            }
+            profile.record(pc);
+        }
+    }
+
+    #[test]
+    fn every_command_and_loop_is_counted() {
+        let profile = profile("++[>+++[-]<-]");
+
+        assert_eq!(
+            profile.get_counts(),
+            [1, 1, 1, 2, 2, 2, 2, 2, 6, 6, 2, 2, 2]
+        );
+        assert_eq!(profile.total_steps(), 31);
+        assert_eq!(
+            profile.loops(),
+            [
+                LoopProfile {
+                    start: 2,
+                    end: 12,
+                    entries: 1,
+                    iterations: 2,
+                    steps: 29,
+                },
+                LoopProfile {
+                    start: 7,
+                    end: 9,
+                    entries: 2,
+                    iterations: 6,
+                    steps: 14,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn commands_past_the_end_are_not_counted() {
+        let mut profile = profile("+");
+        profile.record(1);
+
+        assert_eq!(profile.get_counts(), [1]);
+    }
+
+    #[test]
+    fn reports_list_the_hottest_loops_and_commands_by_position() {
+        let report = profile("++[>+++[-]<-]").report(1);
+
+        assert_eq!(
+            report,
+            "Profile: the program ran for 31 steps.\n\
+             Hottest loops:\n    \
+             1:8 to 1:10: 6 iterations, entered 2 times, 14 steps (45.2%)\n\
+             Hottest commands:\n    \
+             1:9 Decrement: 6 times (19.4%)\n"
+        );
+    }
+
+    #[test]
+    fn reports_leave_out_loops_that_were_never_reached() {
+        let report = profile("[\n[-]]").report(10);
+
+        assert!(report.contains("1:1 to 2:4: 0 iterations, entered 1 times"));
+        assert!(!report.contains("2:1 to"));
+    }
+}
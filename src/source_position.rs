@@ -0,0 +1,11 @@
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+/// Where a command is in the source code. Lines and columns start at 1, and columns count
+/// characters.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[display("{line}:{column}")]
+pub struct SourcePosition {
+    pub line: usize,
+    pub column: usize,
+}
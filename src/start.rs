@@ -1,53 +1,418 @@
-use std::io::{self, Write};
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    process::ExitCode,
+    time::Duration,
+};
 
 use crate::{
     debugger::{
         debugger_state::DebuggerState, debugging_state::DebuggingState,
         parse_error::DebuggerCommandParseError,
     },
-    executor::{executing_state::ExecutionState, executor_state::ExecutorState},
-    parsing_src::translate_into_commands,
+    executor::{
+        executing_state::ExecutionState,
+        execution_error_kind::ExecutionErrorKind,
+        executor_command::ExecutorCommand,
+        executor_config::ExecutorConfig,
+        executor_state::{ExecutionResult, ExecutorState},
+        optimized_command::optimize,
+        optimized_executor_state::OptimizedExecutorState,
+        snapshot::Snapshot,
+        snapshot_error::SnapshotError,
+    },
+    jit::elf_executable::build_executable,
+    parsing_src::{source_positions, translate_into_commands},
+    profiler::{coverage::Coverage, profile::Profile},
+    source_position::SourcePosition,
+    trace::tracer::Tracer,
+    transpiler::{c_code, rust_code, target::Target},
+    wasm::compiler as wasm_compiler,
 };
 
-pub fn execute(cmd: &str) {
+#[cfg(feature = "async")]
+use crate::executor::async_executor::AsyncExecutor;
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+use crate::jit::jit_error::JitError;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+use crate::jit::jit_program::JitProgram;
+
+/// How `execute` runs a program.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Runs the program exactly as written.
+    Interpreter,
+    /// Runs the optimized form of the program.
+    #[default]
+    OptimizedInterpreter,
+    /// Compiles the program into machine code before running it.
+    Jit,
+}
+
+/// Runs a program with the backend. Errors are written to the standard error, together with
+/// where in the source code the program failed, and make the process exit with a failure.
+pub fn execute(cmd: &str, config: ExecutorConfig, backend: Backend) -> ExitCode {
     match translate_into_commands(cmd, false) {
         Ok((commands, _)) => {
-            let mut state = ExecutorState::new(commands);
-            loop {
-                match state.execute_once() {
-                    Ok(ExecutionState::Running) => {}
-                    Ok(ExecutionState::Finished) => {
-                        break;
-                    }
-                    Err(info) => {
-                        println!("{}", info);
-                        break;
+            let positions = source_positions(cmd);
+            match backend {
+                Backend::Interpreter => {
+                    let mut state = ExecutorState::with_config(commands, config);
+                    run_to_end(|| state.execute_once(), &positions)
+                }
+                Backend::OptimizedInterpreter => {
+                    let mut state = OptimizedExecutorState::new(&commands, config);
+                    run_to_end(|| state.execute_once(), &positions)
+                }
+                Backend::Jit => run_compiled(&commands, config, &positions),
+            }
+        }
+        Err(info) => report(info),
+    }
+}
+
+/// Runs a program like `execute` with the interpreter, but on a tokio runtime, reading its input
+/// from `input` and writing its output to `output`.
+#[cfg(feature = "async")]
+pub async fn execute_async<R, W>(cmd: &str, config: ExecutorConfig, input: R, output: W) -> ExitCode
+where
+    R: tokio::io::AsyncRead + Unpin,
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    match translate_into_commands(cmd, false) {
+        Ok((commands, _)) => {
+            let mut executor = AsyncExecutor::new(commands, config, input, output);
+            match executor.run().await {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(info) => report(info.locate(&source_positions(cmd))),
+            }
+        }
+        Err(info) => report(info),
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn run_compiled(
+    commands: &[ExecutorCommand],
+    config: ExecutorConfig,
+    positions: &[SourcePosition],
+) -> ExitCode {
+    match JitProgram::compile(commands, config) {
+        Ok(program) => match program.run(io::stdin(), io::stdout()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(info) => report(info.locate(positions)),
+        },
+        Err(info) => report(info),
+    }
+}
+
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+fn run_compiled(
+    _commands: &[ExecutorCommand],
+    _config: ExecutorConfig,
+    _positions: &[SourcePosition],
+) -> ExitCode {
+    report(JitError::UnsupportedPlatform)
+}
+
+/// The number of loops and commands a profile report shows.
+const PROFILE_REPORT_LENGTH: usize = 10;
+
+/// Runs a program exactly as written while counting how often every command runs, then writes a
+/// report of the hottest loops and commands to the standard error.
+pub fn profile(cmd: &str, config: ExecutorConfig) -> ExitCode {
+    match translate_into_commands(cmd, false) {
+        Ok((commands, _)) => {
+            let positions = source_positions(cmd);
+            let mut profile = Profile::new(&commands, positions.clone());
+            let mut state = ExecutorState::with_config(commands, config);
+            let exit_code = run_to_end(
+                || {
+                    let (pc, steps) = (state.get_pc(), state.get_steps());
+                    let result = state.execute_once();
+                    // A command stopped by a limit has not run.
+                    if state.get_steps() > steps {
+                        profile.record(pc);
                     }
+                    result
+                },
+                &positions,
+            );
+            eprint!("{}", profile.report(PROFILE_REPORT_LENGTH));
+            exit_code
+        }
+        Err(info) => report(info),
+    }
+}
+
+/// Runs a program exactly as written while recording every step into the file at `trace`.
+pub fn trace(cmd: &str, config: ExecutorConfig, trace: &str) -> ExitCode {
+    match translate_into_commands(cmd, false) {
+        Ok((commands, _)) => {
+            let Ok(file) = File::create(trace) else {
+                return report(ExecutionErrorKind::TraceError);
+            };
+            let mut state = ExecutorState::with_config(commands, config);
+            match Tracer::new(BufWriter::new(file)).run(&mut state) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(info) => report(info.locate(&source_positions(cmd))),
+            }
+        }
+        Err(info) => report(info),
+    }
+}
+
+/// Runs a program once for every input file, or once on the standard input if there are none,
+/// and writes its source code annotated with the coverage of all runs to the standard output. The
+/// output of the program is dropped. The coverage is also written in the LCOV format to the file
+/// at `lcov`, if there is one. Failed runs, input files that can not be read and the LCOV file
+/// not being written are reported on the standard error and make the process exit with a
+/// failure, but the coverage of the other runs is still written.
+pub fn coverage(
+    cmd: &str,
+    config: ExecutorConfig,
+    source_path: &str,
+    inputs: &[String],
+    lcov: Option<&str>,
+) -> ExitCode {
+    match translate_into_commands(cmd, false) {
+        Ok((commands, _)) => {
+            let positions = source_positions(cmd);
+            let mut coverage = Coverage::new(positions.clone());
+            let mut exit_code = ExitCode::SUCCESS;
+
+            if inputs.is_empty() {
+                let mut state =
+                    ExecutorState::with_io(commands.clone(), config, io::stdin(), io::sink());
+                if let Err(info) = coverage.record_run(&mut state) {
+                    exit_code = report(info.locate(&positions));
                 }
             }
+            for input in inputs {
+                let Ok(file) = File::open(input) else {
+                    exit_code = report(format!(
+                        "IO Error: Failed to read the input file {}!",
+                        input
+                    ));
+                    continue;
+                };
+                let mut state = ExecutorState::with_io(
+                    commands.clone(),
+                    config,
+                    BufReader::new(file),
+                    io::sink(),
+                );
+                if let Err(info) = coverage.record_run(&mut state) {
+                    exit_code = report(format!("{}: {}", input, info.locate(&positions)));
+                }
+            }
+
+            print!("{}", coverage.annotate(cmd));
+            if let Some(path) = lcov
+                && std::fs::write(path, coverage.to_lcov(source_path)).is_err()
+            {
+                exit_code = report(ExecutionErrorKind::OutputError);
+            }
+            exit_code
         }
-        Err(info) => println!("{}", info),
+        Err(info) => report(info),
+    }
+}
+
+/// Runs a program exactly as written and saves it into the file at `snapshot` if it stops before
+/// finishing, so it can be resumed with `resume`.
+pub fn checkpoint(cmd: &str, config: ExecutorConfig, snapshot: &str) -> ExitCode {
+    match translate_into_commands(cmd, false) {
+        Ok((commands, _)) => run_saving_snapshot(
+            ExecutorState::with_config(commands, config),
+            &source_positions(cmd),
+            Some(snapshot),
+        ),
+        Err(info) => report(info),
+    }
+}
+
+/// Resumes the program saved in the snapshot at `path`. The standard input has to be the input
+/// the program was started with, the part it had already read is skipped. The program runs with
+/// the given limits instead of the ones it was saved with, and is saved again into the file at
+/// `snapshot` if it stops before finishing.
+pub fn resume(
+    path: &str,
+    max_steps: Option<u64>,
+    timeout: Option<Duration>,
+    max_cells: Option<usize>,
+    snapshot: Option<&str>,
+) -> ExitCode {
+    let loaded = File::open(path)
+        .map_err(|_err| SnapshotError::ReadError)
+        .and_then(|file| Snapshot::read(BufReader::new(file)));
+    let mut saved = match loaded {
+        Ok(saved) => saved,
+        Err(info) => return report(info),
     };
+    saved.set_limits(max_steps, timeout, max_cells);
+    if let Err(info) = saved.get_config().check_tape() {
+        return report(info);
+    }
+
+    let mut input = io::stdin();
+    if io::copy(
+        &mut (&mut input).take(saved.get_bytes_read()),
+        &mut io::sink(),
+    )
+    .is_err()
+    {
+        return report(ExecutionErrorKind::InputError);
+    }
+
+    let positions = saved.get_positions().to_vec();
+    run_saving_snapshot(
+        ExecutorState::from_snapshot(saved, input, io::stdout()),
+        &positions,
+        snapshot,
+    )
+}
+
+fn run_saving_snapshot(
+    mut state: ExecutorState,
+    positions: &[SourcePosition],
+    snapshot: Option<&str>,
+) -> ExitCode {
+    loop {
+        match state.execute_once() {
+            Ok(ExecutionState::Running) => {}
+            Ok(ExecutionState::Finished) => return ExitCode::SUCCESS,
+            Err(info) => {
+                let exit_code = report(info.locate(positions));
+                if let Some(path) = snapshot
+                    && let Err(info) = save_snapshot(&state, positions, path)
+                {
+                    return report(info);
+                }
+                return exit_code;
+            }
+        }
+    }
+}
+
+fn save_snapshot(
+    state: &ExecutorState,
+    positions: &[SourcePosition],
+    path: &str,
+) -> Result<(), SnapshotError> {
+    let mut saved = state.snapshot();
+    saved.set_positions(positions.to_vec());
+
+    let file = File::create(path).map_err(|_err| SnapshotError::WriteError)?;
+    saved.write(BufWriter::new(file))
+}
+
+fn run_to_end(
+    mut execute_once: impl FnMut() -> ExecutionResult,
+    positions: &[SourcePosition],
+) -> ExitCode {
+    loop {
+        match execute_once() {
+            Ok(ExecutionState::Running) => {}
+            Ok(ExecutionState::Finished) => {
+                return ExitCode::SUCCESS;
+            }
+            Err(info) => {
+                return report(info.locate(positions));
+            }
+        }
+    }
+}
+
+/// Writes the error to the standard error after everything the program has output so far.
+pub fn report(info: impl Display) -> ExitCode {
+    let _ = io::stdout().flush();
+    eprintln!("{}", info);
+    ExitCode::FAILURE
+}
+
+/// Translates a program into the target language and writes the result to the file at `output`,
+/// or to the standard output if there is none. Errors are written to the standard error.
+pub fn compile(
+    cmd: &str,
+    config: ExecutorConfig,
+    target: Target,
+    output: Option<&str>,
+) -> ExitCode {
+    match translate_into_commands(cmd, false) {
+        Ok((commands, _)) => {
+            let code = match target {
+                Target::C => c_code::transpile(&commands, &config).into_bytes(),
+                Target::Rust => rust_code::transpile(&commands, &config).into_bytes(),
+                Target::Wasm | Target::Wat => {
+                    match wasm_compiler::compile(&optimize(&commands, &config), &config) {
+                        Ok(module) if target == Target::Wasm => module.to_binary(),
+                        Ok(module) => module.to_text().into_bytes(),
+                        Err(info) => return report(info),
+                    }
+                }
+            };
+
+            let written = match output {
+                Some(path) => std::fs::write(path, code),
+                None => io::stdout().write_all(&code),
+            };
+            match written {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(_) => report(ExecutionErrorKind::OutputError),
+            }
+        }
+        Err(info) => report(info),
+    }
+}
+
+/// Builds a program into a static x86-64 Linux executable at `output`. Errors are written to the
+/// standard error.
+pub fn build(cmd: &str, config: ExecutorConfig, output: &str) -> ExitCode {
+    match translate_into_commands(cmd, false) {
+        Ok((commands, _)) => match build_executable(&commands, &config) {
+            Ok(executable) => match write_executable(output, &executable) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(_) => report(ExecutionErrorKind::OutputError),
+            },
+            Err(info) => report(info),
+        },
+        Err(info) => report(info),
+    }
 }
 
-pub fn debug(cmd: &str) {
+fn write_executable(path: &str, executable: &[u8]) -> io::Result<()> {
+    std::fs::write(path, executable)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(())
+}
+
+/// Runs a program in the debugger, reading debugger commands from the standard input until the
+/// user quits. Errors that end the session are written to the standard error.
+pub fn debug(cmd: &str, config: ExecutorConfig) -> ExitCode {
     match translate_into_commands(cmd, true) {
         Ok((commands, breakpoints)) => {
-            let mut debugger = DebuggerState::new(commands, breakpoints);
+            let mut debugger = DebuggerState::new(commands, breakpoints, config);
             loop {
                 print!("Please enter the next command: ");
                 io::stdout().flush().unwrap();
                 let mut input_line = String::new();
                 if io::stdin().read_line(&mut input_line).is_err() {
-                    println!("IO Error: Failed to get command input!");
-                    break;
+                    return report("IO Error: Failed to get command input!");
                 }
 
                 match debugger.execute_debugger_command(input_line) {
                     Ok(result) => match result {
                         Ok(DebuggingState::Finished) => {
                             println!("Quitting the debugger. Bye!");
-                            break;
+                            return ExitCode::SUCCESS;
                         }
                         Err(err) => println!("{}", err),
                         _ => {}
@@ -62,6 +427,6 @@ pub fn debug(cmd: &str) {
                 println!();
             }
         }
-        Err(info) => println!("{}", info),
-    };
+        Err(info) => report(info),
+    }
 }
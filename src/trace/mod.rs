@@ -0,0 +1,5 @@
+pub mod trace_entry;
+pub mod trace_error;
+pub mod trace_event;
+pub mod trace_reader;
+pub mod tracer;
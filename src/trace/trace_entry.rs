@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use crate::executor::executor_state::Int;
+
+use super::trace_event::TraceEvent;
+
+/// A line of a trace. A trace holds a `Step` for every step of the program, followed by
+/// `Finished` or `Error` once it stopped. Every line is an object whose only key names the kind
+/// of the entry, like `{"finished":{"steps":3}}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceEntry {
+    Step {
+        /// The number of the step, starting at 1.
+        step: u64,
+        pc: usize,
+        command: String,
+        /// The data pointer before the step.
+        pointer: isize,
+        /// The value of the cell at `pointer` before the step.
+        before: Int,
+        /// The value of the cell at `pointer` after the step.
+        after: Int,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        event: Option<TraceEvent>,
+    },
+    Finished {
+        steps: u64,
+    },
+    Error {
+        steps: u64,
+        pc: usize,
+        message: String,
+    },
+}
@@ -0,0 +1,9 @@
+use derive_more::Display;
+
+#[derive(Debug, Display)]
+pub enum TraceError {
+    #[display("Trace Error: Unable to read the trace!")]
+    ReadError,
+    #[display("Trace Error: Line {} of the trace is not a valid entry!", _0)]
+    InvalidEntry(usize),
+}
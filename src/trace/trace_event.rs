@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use crate::executor::executor_state::Int;
+
+/// The I/O a step of a traced program performed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// A `,` command read the value.
+    Input(Int),
+    /// A `,` command found that the input had run out.
+    EndOfInput,
+    /// A `.` command wrote the value.
+    Output(Int),
+}
@@ -0,0 +1,39 @@
+use std::io::BufRead;
+
+use super::{trace_entry::TraceEntry, trace_error::TraceError};
+
+/// Reads the entries of a trace written by `Tracer`, one per line.
+pub struct TraceReader<R> {
+    reader: R,
+    line: usize,
+}
+
+impl<R: BufRead> TraceReader<R> {
+    pub fn new(reader: R) -> TraceReader<R> {
+        TraceReader { reader, line: 0 }
+    }
+}
+
+impl<R: BufRead> Iterator for TraceReader<R> {
+    type Item = Result<TraceEntry, TraceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut text = String::new();
+
+        loop {
+            self.line += 1;
+            text.clear();
+            match self.reader.read_line(&mut text) {
+                Ok(0) => return None,
+                Ok(_) if text.trim().is_empty() => {}
+                Ok(_) => {
+                    return Some(
+                        serde_json::from_str(&text)
+                            .map_err(|_err| TraceError::InvalidEntry(self.line)),
+                    );
+                }
+                Err(_) => return Some(Err(TraceError::ReadError)),
+            }
+        }
+    }
+}
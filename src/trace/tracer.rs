@@ -0,0 +1,173 @@
+use std::io::{Read, Write};
+
+use crate::executor::{
+    executing_error::ExecutionError, executing_state::ExecutionState,
+    execution_error_kind::ExecutionErrorKind, executor_command::ExecutorCommand,
+    executor_state::ExecutorState,
+};
+
+use super::{trace_entry::TraceEntry, trace_event::TraceEvent};
+
+/// Records every step of a program as a line of JSON, see `TraceEntry`.
+pub struct Tracer<T> {
+    trace: T,
+}
+
+impl<T: Write> Tracer<T> {
+    pub fn new(trace: T) -> Tracer<T> {
+        Tracer { trace }
+    }
+
+    /// Runs the program to its end, recording every step and how the program stopped.
+    pub fn run<R: Read, W: Write>(
+        &mut self,
+        state: &mut ExecutorState<R, W>,
+    ) -> Result<(), ExecutionError> {
+        loop {
+            let (pc, pointer) = (state.get_pc(), state.get_pointer());
+            let (steps, bytes_read) = (state.get_steps(), state.get_bytes_read());
+            let before = state.get_cell(pointer).unwrap_or_default();
+
+            let result = state.execute_once();
+            let entry = match &result {
+                Ok(ExecutionState::Running) => {
+                    let after = state.get_cell(pointer).unwrap_or_default();
+                    let command = state.get_command(pc);
+                    let event = match command {
+                        Some(ExecutorCommand::Input) if state.get_bytes_read() == bytes_read => {
+                            Some(TraceEvent::EndOfInput)
+                        }
+                        Some(ExecutorCommand::Input) => Some(TraceEvent::Input(after)),
+                        Some(ExecutorCommand::Output) => Some(TraceEvent::Output(before)),
+                        _ => None,
+                    };
+
+                    TraceEntry::Step {
+                        step: state.get_steps(),
+                        pc,
+                        command: command
+                            .map(|command| command.to_string())
+                            .unwrap_or_default(),
+                        pointer,
+                        before,
+                        after,
+                        event,
+                    }
+                }
+                Ok(ExecutionState::Finished) => TraceEntry::Finished { steps },
+                Err(err) => TraceEntry::Error {
+                    steps,
+                    pc,
+                    message: err.get_kind().to_string(),
+                },
+            };
+            self.write(&entry)?;
+
+            match result {
+                Ok(ExecutionState::Running) => {}
+                Ok(ExecutionState::Finished) => return Ok(self.flush()?),
+                Err(err) => {
+                    self.flush()?;
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), ExecutionErrorKind> {
+        self.trace
+            .flush()
+            .map_err(|_err| ExecutionErrorKind::TraceError)
+    }
+
+    fn write(&mut self, entry: &TraceEntry) -> Result<(), ExecutionErrorKind> {
+        serde_json::to_writer(&mut self.trace, entry)
+            .map_err(|_err| ExecutionErrorKind::TraceError)?;
+        self.trace
+            .write_all(b"\n")
+            .map_err(|_err| ExecutionErrorKind::TraceError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executor::{
+            eof_policy::EofPolicy, executing_error::ExecutionError,
+            execution_error_kind::ExecutionErrorKind, executor_config::ExecutorConfig,
+            executor_state::ExecutorState,
+        },
+        parsing_src::translate_into_commands,
+        trace::{trace_entry::TraceEntry, trace_event::TraceEvent, trace_reader::TraceReader},
+    };
+
+    use super::Tracer;
+
+    fn trace(source: &str, input: &[u8]) -> (Result<(), ExecutionError>, Vec<TraceEntry>) {
+        let (commands, _) = translate_into_commands(source, false).unwrap();
+        let config = ExecutorConfig {
+            eof_policy: EofPolicy::Zero,
+            ..ExecutorConfig::default()
+        };
+        let mut state = ExecutorState::with_io(commands, config, input, Vec::new());
+
+        let mut trace = Vec::new();
+        let result = Tracer::new(&mut trace).run(&mut state);
+        let entries = TraceReader::new(trace.as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        (result, entries)
+    }
+
+    fn step(
+        step: u64,
+        command: &str,
+        before: i128,
+        after: i128,
+        event: Option<TraceEvent>,
+    ) -> TraceEntry {
+        TraceEntry::Step {
+            step,
+            pc: step as usize - 1,
+            command: command.to_string(),
+            pointer: 0,
+            before,
+            after,
+            event,
+        }
+    }
+
+    #[test]
+    fn traces_read_back_into_their_steps_and_error() {
+        let (result, entries) = trace(",+.,.<", b"h");
+
+        assert_eq!(
+            result.unwrap_err().get_kind(),
+            ExecutionErrorKind::TooFarLeft
+        );
+        assert_eq!(
+            entries,
+            [
+                step(1, "Input", 0, 104, Some(TraceEvent::Input(104))),
+                step(2, "Increment", 104, 105, None),
+                step(3, "Output", 105, 105, Some(TraceEvent::Output(105))),
+                step(4, "Input", 105, 0, Some(TraceEvent::EndOfInput)),
+                step(5, "Output", 0, 0, Some(TraceEvent::Output(0))),
+                TraceEntry::Error {
+                    steps: 5,
+                    pc: 5,
+                    message: ExecutionErrorKind::TooFarLeft.to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn traces_of_finished_programs_end_with_their_steps() {
+        let (result, entries) = trace("+[-]", b"");
+
+        assert!(result.is_ok());
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries.last(), Some(&TraceEntry::Finished { steps: 4 }));
+    }
+}
@@ -0,0 +1,494 @@
+use crate::executor::{
+    cell_type::CellType,
+    eof_policy::EofPolicy,
+    execution_error_kind::ExecutionErrorKind,
+    executor_command::ExecutorCommand,
+    executor_config::ExecutorConfig,
+    executor_state::Int,
+    io_mode::IoMode,
+    optimized_command::{OptimizedCommand, optimize},
+    overflow_policy::OverflowPolicy,
+    tape::TapeMode,
+};
+
+use super::source_writer::SourceWriter;
+
+/// Translates a program into a standalone C program that behaves exactly like the program run by
+/// `ExecutorState` with the same configuration. It reads from the standard input, writes to the
+/// standard output, and writes the error to the standard error and exits with status 1 if the
+/// program fails.
+///
+/// The generated code needs a compiler supporting `__builtin_add_overflow`, like GCC or Clang,
+/// and `__int128` for `i128` cells.
+pub fn transpile(commands: &[ExecutorCommand], config: &ExecutorConfig) -> String {
+    let mut writer = SourceWriter::new();
+
+    writer.line("/*");
+    writer.line(" * Generated by bfdbg.");
+    writer.line(&format!(
+        " * Cells: {}, overflow: {}, tape: {}, EOF: {}, I/O: {}.",
+        config.cell_type,
+        config.overflow_policy,
+        config.tape_mode,
+        config.eof_policy,
+        config.io_mode
+    ));
+    writer.line(" */");
+    writer.line("");
+    writer.lines(INCLUDES);
+    writer.line("");
+    write_types(&mut writer, config.cell_type);
+    writer.line("");
+    writer.lines(&format!(
+        "_Noreturn static void fail(const char *message) {{
+    fflush(stdout);
+    fprintf(stderr, \"%s\\n\", message);
+    exit(1);
+}}
+
+static void flush(void) {{
+    if (fflush(stdout) == EOF) {{
+        fail({});
+    }}
+}}",
+        message(ExecutionErrorKind::OutputError)
+    ));
+    writer.line("");
+    write_tape(&mut writer, config);
+    writer.line("");
+    write_move_pointer(&mut writer, config.tape_mode);
+    writer.line("");
+    write_add(&mut writer, config.overflow_policy);
+    writer.line("");
+    write_output(&mut writer, config.io_mode);
+    writer.line("");
+    write_input(&mut writer, config);
+    writer.line("");
+
+    writer.line("int main(void) {");
+    writer.indent();
+    if let TapeMode::Growable | TapeMode::Bidirectional = config.tape_mode {
+        writer.line("grow(0, 0);");
+    }
+    for command in optimize(commands, config) {
+        write_command(&mut writer, &command, config);
+    }
+    writer.line("flush();");
+    writer.line("return 0;");
+    writer.dedent();
+    writer.line("}");
+
+    writer.finish()
+}
+
+const INCLUDES: &str = "#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>";
+
+/// Writes the type of a cell, its unsigned counterpart, the wider types the arithmetic is done in
+/// and the bounds of a cell.
+fn write_types(writer: &mut SourceWriter, cell_type: CellType) {
+    let (cell, unsigned_cell) = match cell_type {
+        CellType::U8 => ("uint8_t", "uint8_t"),
+        CellType::U16 => ("uint16_t", "uint16_t"),
+        CellType::U32 => ("uint32_t", "uint32_t"),
+        CellType::I32 => ("int32_t", "uint32_t"),
+        CellType::I64 => ("int64_t", "uint64_t"),
+        CellType::I128 => ("__int128", "unsigned __int128"),
+    };
+    let (wide, unsigned_wide) = match cell_type {
+        CellType::I128 => ("__int128", "unsigned __int128"),
+        _ => ("int64_t", "uint64_t"),
+    };
+
+    writer.line(&format!("typedef {cell} cell;"));
+    writer.line(&format!("typedef {unsigned_cell} ucell;"));
+    writer.line(&format!("typedef {wide} wide;"));
+    writer.line(&format!("typedef {unsigned_wide} uwide;"));
+    writer.line("");
+    match cell_type {
+        CellType::I128 => {
+            writer.line("#define CELL_MAX ((cell)(((uwide)1 << 127) - 1))");
+            writer.line("#define CELL_MIN (-CELL_MAX - 1)");
+        }
+        _ => {
+            writer.line(&format!(
+                "#define CELL_MAX ((cell){})",
+                literal(cell_type.max_value())
+            ));
+            writer.line(&format!(
+                "#define CELL_MIN ((cell){})",
+                literal(cell_type.min_value())
+            ));
+        }
+    }
+}
+
+fn write_tape(writer: &mut SourceWriter, config: &ExecutorConfig) {
+    match config.tape_mode {
+        TapeMode::Growable | TapeMode::Bidirectional => {
+            writer.lines(
+                "static cell *tape;
+static size_t length;
+static size_t position;
+
+/* Makes room for at least `needed` more cells, in front of the others if `front` is set. */
+static void grow(size_t needed, int front) {
+    size_t extra = needed > length ? needed : length;
+    if (extra < 32) {
+        extra = 32;
+    }
+
+    cell *grown = calloc(length + extra, sizeof(cell));
+    if (grown == NULL) {
+        fail(\"Memory Error: Unable to grow the tape!\");
+    }
+    if (length > 0) {
+        memcpy(grown + (front ? extra : 0), tape, length * sizeof(cell));
+    }
+    free(tape);
+
+    tape = grown;
+    length += extra;
+    if (front) {
+        position += extra;
+    }
+}",
+            );
+        }
+        TapeMode::Fixed | TapeMode::Circular => {
+            writer.line(&format!(
+                "#define LENGTH ((size_t){})",
+                config.tape_length.max(1)
+            ));
+            writer.line("");
+            writer.line("static cell tape[LENGTH];");
+            writer.line("static size_t position;");
+        }
+    }
+}
+
+/// Writes `move_pointer`, which moves the data pointer by an offset and grows or wraps around the
+/// tape exactly like a sequence of `>` or `<` commands does.
+fn write_move_pointer(writer: &mut SourceWriter, tape_mode: TapeMode) {
+    let too_far_left = message(ExecutionErrorKind::TooFarLeft);
+    let too_far_right = message(ExecutionErrorKind::TooFarRight);
+
+    writer.line("static inline void move_pointer(long long offset) {");
+    writer.indent();
+    match tape_mode {
+        TapeMode::Growable => writer.lines(&format!(
+            "if (offset < 0 && (size_t)-offset > position) {{
+    fail({too_far_left});
+}}
+position += (size_t)offset;
+if (position >= length) {{
+    grow(position - length + 1, 0);
+}}"
+        )),
+        TapeMode::Bidirectional => writer.lines(
+            "if (offset < 0 && (size_t)-offset > position) {
+    grow((size_t)-offset - position, 1);
+}
+position += (size_t)offset;
+if (position >= length) {
+    grow(position - length + 1, 0);
+}",
+        ),
+        TapeMode::Fixed => writer.lines(&format!(
+            "if (offset < 0 && (size_t)-offset > position) {{
+    fail({too_far_left});
+}}
+if (offset > 0 && (size_t)offset >= LENGTH - position) {{
+    fail({too_far_right});
+}}
+position += (size_t)offset;"
+        )),
+        TapeMode::Circular => writer.lines(
+            "long long steps = offset % (long long)LENGTH;
+if (steps < 0) {
+    steps += (long long)LENGTH;
+}
+position = (position + (size_t)steps) % LENGTH;",
+        ),
+    }
+    writer.dedent();
+    writer.line("}");
+}
+
+/// Writes `add`, which adds a value to the current cell according to the overflow policy. The
+/// value is a `uwide` if cells wrap around and a `wide` otherwise.
+fn write_add(writer: &mut SourceWriter, overflow_policy: OverflowPolicy) {
+    match overflow_policy {
+        OverflowPolicy::Wrap => writer.lines(
+            "static inline void add(uwide delta) {
+    tape[position] = (cell)(ucell)((uwide)tape[position] + delta);
+}",
+        ),
+        OverflowPolicy::Error => writer.lines(&format!(
+            "static inline void add(wide delta) {{
+    cell value;
+    if (__builtin_add_overflow(tape[position], delta, &value)) {{
+        fail(delta > 0 ? {} : {});
+    }}
+    tape[position] = value;
+}}",
+            message(ExecutionErrorKind::Overflow),
+            message(ExecutionErrorKind::Underflow)
+        )),
+        OverflowPolicy::Saturate => writer.lines(
+            "static inline void add(wide delta) {
+    cell value;
+    if (__builtin_add_overflow(tape[position], delta, &value)) {
+        value = delta > 0 ? CELL_MAX : CELL_MIN;
+    }
+    tape[position] = value;
+}",
+        ),
+    }
+}
+
+fn write_output(writer: &mut SourceWriter, io_mode: IoMode) {
+    let output_error = message(ExecutionErrorKind::OutputError);
+
+    match io_mode {
+        IoMode::Byte => writer.lines(&format!(
+            "static inline void output(void) {{
+    if (putchar((unsigned char)tape[position]) == EOF) {{
+        fail({output_error});
+    }}
+}}"
+        )),
+        IoMode::Unicode => writer.lines(&format!(
+            "static inline void output(void) {{
+    wide value = (wide)tape[position];
+    if (value < 0 || value > 0x10FFFF || (value >= 0xD800 && value <= 0xDFFF)) {{
+        fail({});
+    }}
+
+    unsigned char bytes[4];
+    size_t count;
+    if (value < 0x80) {{
+        bytes[0] = (unsigned char)value;
+        count = 1;
+    }} else if (value < 0x800) {{
+        bytes[0] = (unsigned char)(0xC0 | value >> 6);
+        bytes[1] = (unsigned char)(0x80 | (value & 0x3F));
+        count = 2;
+    }} else if (value < 0x10000) {{
+        bytes[0] = (unsigned char)(0xE0 | value >> 12);
+        bytes[1] = (unsigned char)(0x80 | (value >> 6 & 0x3F));
+        bytes[2] = (unsigned char)(0x80 | (value & 0x3F));
+        count = 3;
+    }} else {{
+        bytes[0] = (unsigned char)(0xF0 | value >> 18);
+        bytes[1] = (unsigned char)(0x80 | (value >> 12 & 0x3F));
+        bytes[2] = (unsigned char)(0x80 | (value >> 6 & 0x3F));
+        bytes[3] = (unsigned char)(0x80 | (value & 0x3F));
+        count = 4;
+    }}
+
+    if (fwrite(bytes, 1, count, stdout) != count) {{
+        fail({output_error});
+    }}
+}}",
+            message(ExecutionErrorKind::InvalidCharacter)
+        )),
+    }
+}
+
+/// Writes `read_char`, which reads the next character in the I/O mode or gives back -1 once the
+/// input has run out, and `input`, which stores it in the current cell.
+fn write_input(writer: &mut SourceWriter, config: &ExecutorConfig) {
+    let invalid_input = message(ExecutionErrorKind::InvalidInput);
+
+    writer.lines(&format!(
+        "static inline int read_byte(void) {{
+    int byte = getchar();
+    if (byte == EOF && ferror(stdin)) {{
+        fail({});
+    }}
+    return byte;
+}}",
+        message(ExecutionErrorKind::InputError)
+    ));
+    writer.line("");
+
+    match config.io_mode {
+        IoMode::Byte => writer.lines(
+            "static inline wide read_char(void) {
+    return read_byte();
+}",
+        ),
+        IoMode::Unicode => writer.lines(&format!(
+            "static inline wide read_char(void) {{
+    int first = read_byte();
+    if (first == EOF) {{
+        return -1;
+    }}
+
+    size_t count;
+    wide value;
+    if (first <= 0x7F) {{
+        return first;
+    }} else if (first >= 0xC2 && first <= 0xDF) {{
+        count = 2;
+        value = first & 0x1F;
+    }} else if (first >= 0xE0 && first <= 0xEF) {{
+        count = 3;
+        value = first & 0x0F;
+    }} else if (first >= 0xF0 && first <= 0xF4) {{
+        count = 4;
+        value = first & 0x07;
+    }} else {{
+        fail({invalid_input});
+    }}
+
+    int bytes[4] = {{first}};
+    for (size_t i = 1; i < count; i++) {{
+        bytes[i] = read_byte();
+        if (bytes[i] == EOF) {{
+            fail({invalid_input});
+        }}
+    }}
+
+    /* The second byte is restricted further to rule out overlong encodings, surrogates and
+       values past 0x10FFFF. */
+    int low = 0x80;
+    int high = 0xBF;
+    if (first == 0xE0) {{
+        low = 0xA0;
+    }} else if (first == 0xED) {{
+        high = 0x9F;
+    }} else if (first == 0xF0) {{
+        low = 0x90;
+    }} else if (first == 0xF4) {{
+        high = 0x8F;
+    }}
+    if (bytes[1] < low || bytes[1] > high) {{
+        fail({invalid_input});
+    }}
+
+    for (size_t i = 1; i < count; i++) {{
+        if ((bytes[i] & 0xC0) != 0x80) {{
+            fail({invalid_input});
+        }}
+        value = value << 6 | (bytes[i] & 0x3F);
+    }}
+    return value;
+}}"
+        )),
+    }
+    writer.line("");
+
+    writer.line("static inline void input(void) {");
+    writer.indent();
+    writer.line("flush();");
+    writer.line("wide value = read_char();");
+    writer.line("if (value < 0) {");
+    writer.indent();
+    match config.eof_policy {
+        EofPolicy::Zero => writer.line("tape[position] = 0;"),
+        EofPolicy::MinusOne => writer.line(&format!(
+            "tape[position] = (cell){};",
+            literal(config.cell_type.wrap(-1))
+        )),
+        EofPolicy::Unchanged => {}
+        EofPolicy::Error => writer.line(&format!(
+            "fail({});",
+            message(ExecutionErrorKind::InputError)
+        )),
+    }
+    writer.line("return;");
+    writer.dedent();
+    writer.line("}");
+
+    let largest_input = match config.io_mode {
+        IoMode::Byte => 0xff,
+        IoMode::Unicode => char::MAX as Int,
+    };
+    if !config.cell_type.contains(largest_input) {
+        writer.line("if (value > (wide)CELL_MAX) {");
+        writer.indent();
+        match config.overflow_policy {
+            OverflowPolicy::Error => {
+                writer.line(&format!("fail({});", message(ExecutionErrorKind::Overflow)))
+            }
+            OverflowPolicy::Wrap => writer.line("value = (wide)(cell)(ucell)value;"),
+            OverflowPolicy::Saturate => writer.line("value = (wide)CELL_MAX;"),
+        }
+        writer.dedent();
+        writer.line("}");
+    }
+    writer.line("tape[position] = (cell)value;");
+    writer.dedent();
+    writer.line("}");
+}
+
+fn write_command(writer: &mut SourceWriter, command: &OptimizedCommand, config: &ExecutorConfig) {
+    let wrapping = config.overflow_policy == OverflowPolicy::Wrap;
+
+    match command {
+        OptimizedCommand::Add(delta) => {
+            // Cells wrap around modulo their width, so the delta can be wrapped the same way.
+            if wrapping {
+                writer.line(&format!(
+                    "add((uwide){});",
+                    literal(config.cell_type.wrap(*delta))
+                ));
+            } else {
+                writer.line(&format!("add({});", literal(*delta)));
+            }
+        }
+        OptimizedCommand::Move(offset) => writer.line(&format!("move_pointer({offset});")),
+        OptimizedCommand::Clear => writer.line("tape[position] = 0;"),
+        OptimizedCommand::Scan(offset) => {
+            writer.line(&format!("while (tape[position]) move_pointer({offset});"));
+        }
+        OptimizedCommand::Multiply(factors) => {
+            writer.line("if (tape[position]) {");
+            writer.indent();
+            writer.line("uwide value = (uwide)tape[position];");
+            for (offset, factor) in factors {
+                writer.line(&format!("move_pointer({offset});"));
+                writer.line(&format!(
+                    "add(value * (uwide){});",
+                    literal(config.cell_type.wrap(*factor))
+                ));
+                writer.line(&format!("move_pointer({});", -offset));
+            }
+            writer.line("tape[position] = 0;");
+            writer.dedent();
+            writer.line("}");
+        }
+        OptimizedCommand::Output => writer.line("output();"),
+        OptimizedCommand::Input => writer.line("input();"),
+        OptimizedCommand::JumpForward(_) => {
+            writer.line("while (tape[position]) {");
+            writer.indent();
+        }
+        OptimizedCommand::JumpBack(_) => {
+            writer.dedent();
+            writer.line("}");
+        }
+    }
+}
+
+/// Writes a value as a C integer constant. Values that do not fit into 64 bits are only used for
+/// `i128` cells, where `uwide` is 128 bits wide.
+fn literal(value: Int) -> String {
+    match i64::try_from(value) {
+        Ok(i64::MIN) => String::from("(-9223372036854775807LL - 1)"),
+        Ok(value) => format!("{value}LL"),
+        Err(_) => format!(
+            "(wide)((uwide){}ULL << 64 | {}ULL)",
+            (value >> 64) as u64,
+            value as u64
+        ),
+    }
+}
+
+fn message(error: ExecutionErrorKind) -> String {
+    format!("{:?}", error.to_string())
+}
@@ -0,0 +1,4 @@
+pub mod c_code;
+pub mod rust_code;
+pub mod source_writer;
+pub mod target;
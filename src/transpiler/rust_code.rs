@@ -0,0 +1,453 @@
+use std::{fs, io, path::Path};
+
+use crate::{
+    executor::{
+        cell_type::CellType,
+        eof_policy::EofPolicy,
+        execution_error_kind::ExecutionErrorKind,
+        executor_command::ExecutorCommand,
+        executor_config::ExecutorConfig,
+        executor_state::Int,
+        io_mode::IoMode,
+        optimized_command::{OptimizedCommand, optimize},
+        overflow_policy::OverflowPolicy,
+        tape::TapeMode,
+    },
+    parsing_src::translate_into_commands,
+};
+
+use super::source_writer::SourceWriter;
+
+/// Translates a program into a standalone Rust program that behaves exactly like the program run
+/// by `ExecutorState` with the same configuration. It reads from the standard input, writes to the
+/// standard output, and writes the error to the standard error and exits with status 1 if the
+/// program fails.
+pub fn transpile(commands: &[ExecutorCommand], config: &ExecutorConfig) -> String {
+    let mut code = transpile_module(commands, config);
+    code.push_str(MAIN);
+    code
+}
+
+/// Translates a program into Rust code containing a single function
+/// `pub fn run(input: &mut impl Read, output: &mut impl Write) -> Result<(), &'static str>`,
+/// which behaves exactly like the program run by `ExecutorState` with the same configuration and
+/// gives back the error message if the program fails.
+pub fn transpile_module(commands: &[ExecutorCommand], config: &ExecutorConfig) -> String {
+    let mut writer = SourceWriter::new();
+
+    writer.line("// Generated by bfdbg.");
+    writer.line(&format!(
+        "// Cells: {}, overflow: {}, tape: {}, EOF: {}, I/O: {}.",
+        config.cell_type,
+        config.overflow_policy,
+        config.tape_mode,
+        config.eof_policy,
+        config.io_mode
+    ));
+    writer.line("");
+    writer.line("/// Runs the program, reading from `input` and writing to `output`.");
+    writer.line("pub fn run(");
+    writer.line("    input: &mut impl std::io::Read,");
+    writer.line("    output: &mut impl std::io::Write,");
+    writer.line(") -> Result<(), &'static str> {");
+    writer.indent();
+
+    writer.line("use std::convert::TryFrom;");
+    writer.line("use std::io::{ErrorKind, Read, Write};");
+    writer.line("");
+    writer.line(&format!("type Cell = {};", config.cell_type));
+    writer.line("");
+    write_machine(&mut writer, config);
+    writer.line("");
+
+    writer.line("let mut m = Machine {");
+    writer.indent();
+    match config.tape_mode {
+        TapeMode::Growable | TapeMode::Bidirectional => {
+            writer.line("tape: std::collections::VecDeque::from([0]),")
+        }
+        TapeMode::Fixed | TapeMode::Circular => {
+            writer.line(&format!("tape: vec![0; {}],", config.tape_length.max(1)))
+        }
+    }
+    writer.line("position: 0,");
+    writer.line("input,");
+    writer.line("output,");
+    writer.dedent();
+    writer.line("};");
+    writer.line("");
+
+    for command in optimize(commands, config) {
+        write_command(&mut writer, &command, config);
+    }
+    writer.line("m.flush()");
+
+    writer.dedent();
+    writer.line("}");
+
+    writer.finish()
+}
+
+/// Translates the brainfuck program in the file at `source` into a Rust module as described in
+/// `transpile_module`, and writes it to the file at `destination`. Meant to be called from a build
+/// script, where the module can then be included with
+/// `include!(concat!(env!("OUT_DIR"), "/<destination>"))`.
+pub fn build_module(
+    source: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+    config: &ExecutorConfig,
+) -> io::Result<()> {
+    println!("cargo:rerun-if-changed={}", source.as_ref().display());
+
+    let source_code = fs::read_to_string(source)?;
+    let (commands, _) = translate_into_commands(&source_code, false)
+        .map_err(|info| io::Error::new(io::ErrorKind::InvalidData, info))?;
+
+    fs::write(destination, transpile_module(&commands, config))
+}
+
+const MAIN: &str = "
+fn main() {
+    if let Err(message) = run(&mut std::io::stdin(), &mut std::io::stdout()) {
+        let _ = std::io::Write::flush(&mut std::io::stdout());
+        eprintln!(\"{message}\");
+        std::process::exit(1);
+    }
+}
+";
+
+/// Writes the `Machine` holding the tape and the I/O, together with the methods the commands are
+/// translated into.
+fn write_machine(writer: &mut SourceWriter, config: &ExecutorConfig) {
+    let tape = match config.tape_mode {
+        TapeMode::Growable | TapeMode::Bidirectional => "std::collections::VecDeque<Cell>",
+        TapeMode::Fixed | TapeMode::Circular => "Vec<Cell>",
+    };
+    writer.lines(&format!(
+        "struct Machine<'a, R, W> {{
+    tape: {tape},
+    position: usize,
+    input: &'a mut R,
+    output: &'a mut W,
+}}"
+    ));
+    writer.line("");
+
+    writer.line("#[allow(dead_code)]");
+    writer.line("impl<R: Read, W: Write> Machine<'_, R, W> {");
+    writer.indent();
+    writer.lines(
+        "fn cell(&self) -> Cell {
+    self.tape[self.position]
+}
+
+fn set_cell(&mut self, value: Cell) {
+    self.tape[self.position] = value;
+}",
+    );
+    writer.line("");
+    write_move_pointer(writer, config);
+    writer.line("");
+    write_add(writer, config);
+    writer.line("");
+    write_output(writer, config);
+    writer.line("");
+    write_input(writer, config);
+    writer.line("");
+    writer.lines(&format!(
+        "fn flush(&mut self) -> Result<(), &'static str> {{
+    self.output.flush().map_err(|_| {})
+}}",
+        message(ExecutionErrorKind::OutputError)
+    ));
+    writer.dedent();
+    writer.line("}");
+}
+
+/// Writes `move_pointer`, which moves the data pointer by an offset and grows or wraps around the
+/// tape exactly like a sequence of `>` or `<` commands does.
+fn write_move_pointer(writer: &mut SourceWriter, config: &ExecutorConfig) {
+    let too_far_left = message(ExecutionErrorKind::TooFarLeft);
+    let too_far_right = message(ExecutionErrorKind::TooFarRight);
+
+    writer.line("fn move_pointer(&mut self, offset: isize) -> Result<(), &'static str> {");
+    writer.indent();
+    match config.tape_mode {
+        TapeMode::Growable => writer.lines(&format!(
+            "self.position = self
+    .position
+    .checked_add_signed(offset)
+    .ok_or({too_far_left})?;
+if self.position >= self.tape.len() {{
+    self.tape.resize(self.position + 1, 0);
+}}
+Ok(())"
+        )),
+        TapeMode::Bidirectional => writer.lines(
+            "if offset < 0 && offset.unsigned_abs() > self.position {
+    for _ in self.position..offset.unsigned_abs() {
+        self.tape.push_front(0);
+        self.position += 1;
+    }
+}
+self.position = self.position.wrapping_add_signed(offset);
+if self.position >= self.tape.len() {
+    self.tape.resize(self.position + 1, 0);
+}
+Ok(())",
+        ),
+        TapeMode::Fixed => writer.lines(&format!(
+            "let position = self
+    .position
+    .checked_add_signed(offset)
+    .ok_or({too_far_left})?;
+if position >= self.tape.len() {{
+    return Err({too_far_right});
+}}
+self.position = position;
+Ok(())"
+        )),
+        TapeMode::Circular => writer.lines(
+            "let length = self.tape.len() as isize;
+self.position = (self.position as isize + offset.rem_euclid(length)).rem_euclid(length) as usize;
+Ok(())",
+        ),
+    }
+    writer.dedent();
+    writer.line("}");
+}
+
+/// Writes `add`, which adds a value to the current cell according to the overflow policy. The
+/// value is a `Cell` if cells wrap around and an `i128` otherwise.
+fn write_add(writer: &mut SourceWriter, config: &ExecutorConfig) {
+    let overflow = message(ExecutionErrorKind::Overflow);
+    let underflow = message(ExecutionErrorKind::Underflow);
+
+    writer.lines(&match (config.overflow_policy, config.cell_type) {
+        (OverflowPolicy::Wrap, _) => String::from(
+            "fn add(&mut self, delta: Cell) -> Result<(), &'static str> {
+    self.set_cell(self.cell().wrapping_add(delta));
+    Ok(())
+}",
+        ),
+        (OverflowPolicy::Error, CellType::I128) => format!(
+            "fn add(&mut self, delta: i128) -> Result<(), &'static str> {{
+    let value = self
+        .cell()
+        .checked_add(delta)
+        .ok_or(if delta > 0 {{ {overflow} }} else {{ {underflow} }})?;
+    self.set_cell(value);
+    Ok(())
+}}"
+        ),
+        (OverflowPolicy::Error, _) => format!(
+            "fn add(&mut self, delta: i128) -> Result<(), &'static str> {{
+    let value = Cell::try_from(i128::from(self.cell()) + delta)
+        .map_err(|_| if delta > 0 {{ {overflow} }} else {{ {underflow} }})?;
+    self.set_cell(value);
+    Ok(())
+}}"
+        ),
+        (OverflowPolicy::Saturate, CellType::I128) => String::from(
+            "fn add(&mut self, delta: i128) -> Result<(), &'static str> {
+    self.set_cell(self.cell().saturating_add(delta));
+    Ok(())
+}",
+        ),
+        (OverflowPolicy::Saturate, _) => String::from(
+            "fn add(&mut self, delta: i128) -> Result<(), &'static str> {
+    let value = (i128::from(self.cell()) + delta)
+        .clamp(i128::from(Cell::MIN), i128::from(Cell::MAX));
+    self.set_cell(value as Cell);
+    Ok(())
+}",
+        ),
+    });
+}
+
+fn write_output(writer: &mut SourceWriter, config: &ExecutorConfig) {
+    let output_error = message(ExecutionErrorKind::OutputError);
+
+    match config.io_mode {
+        IoMode::Byte => {
+            let byte = match config.cell_type {
+                CellType::U8 => "self.cell()",
+                _ => "self.cell() as u8",
+            };
+            writer.lines(&format!(
+                "fn output(&mut self) -> Result<(), &'static str> {{
+    self.output
+        .write_all(&[{byte}])
+        .map_err(|_| {output_error})
+}}"
+            ))
+        }
+        IoMode::Unicode => {
+            // The conversion into a `u32` is written differently depending on whether it can fail.
+            let code_point = match config.cell_type {
+                CellType::U8 | CellType::U16 => "Some(u32::from(self.cell()))",
+                CellType::U32 => "Some(self.cell())",
+                _ => "u32::try_from(self.cell()).ok()",
+            };
+            writer.lines(&format!(
+                "fn output(&mut self) -> Result<(), &'static str> {{
+    let character = {code_point}
+        .and_then(char::from_u32)
+        .ok_or({})?;
+    self.output
+        .write_all(character.encode_utf8(&mut [0; 4]).as_bytes())
+        .map_err(|_| {output_error})
+}}",
+                message(ExecutionErrorKind::InvalidCharacter)
+            ))
+        }
+    }
+}
+
+/// Writes `read_char`, which reads the next character in the I/O mode or gives back `None` once
+/// the input has run out, and `input`, which stores it in the current cell.
+fn write_input(writer: &mut SourceWriter, config: &ExecutorConfig) {
+    let invalid_input = message(ExecutionErrorKind::InvalidInput);
+
+    writer.lines(&format!(
+        "fn read_byte(&mut self) -> Result<Option<u8>, &'static str> {{
+    let mut byte = [0];
+    loop {{
+        match self.input.read(&mut byte) {{
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            Err(err) if err.kind() == ErrorKind::Interrupted => {{}}
+            Err(_) => return Err({}),
+        }}
+    }}
+}}",
+        message(ExecutionErrorKind::InputError)
+    ));
+    writer.line("");
+
+    match config.io_mode {
+        IoMode::Byte => writer.lines(
+            "fn read_char(&mut self) -> Result<Option<u32>, &'static str> {
+    Ok(self.read_byte()?.map(u32::from))
+}",
+        ),
+        IoMode::Unicode => writer.lines(&format!(
+            "fn read_char(&mut self) -> Result<Option<u32>, &'static str> {{
+    let Some(first) = self.read_byte()? else {{
+        return Ok(None);
+    }};
+    let length = match first {{
+        0x00..=0x7f => 1,
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => return Err({invalid_input}),
+    }};
+
+    let mut bytes = [first, 0, 0, 0];
+    for byte in bytes.iter_mut().take(length).skip(1) {{
+        *byte = self.read_byte()?.ok_or({invalid_input})?;
+    }}
+
+    let decoded = std::str::from_utf8(&bytes[..length]).map_err(|_| {invalid_input})?;
+    Ok(decoded.chars().next().map(u32::from))
+}}"
+        )),
+    }
+    writer.line("");
+
+    writer.line("fn input(&mut self) -> Result<(), &'static str> {");
+    writer.indent();
+    writer.line("self.flush()?;");
+    writer.line("let Some(value) = self.read_char()? else {");
+    writer.indent();
+    match config.eof_policy {
+        EofPolicy::Zero => writer.line("self.set_cell(0);"),
+        EofPolicy::MinusOne => {
+            writer.line(&format!("self.set_cell({});", config.cell_type.wrap(-1)))
+        }
+        EofPolicy::Unchanged => {}
+        EofPolicy::Error => writer.line(&format!(
+            "return Err({});",
+            message(ExecutionErrorKind::InputError)
+        )),
+    }
+    if config.eof_policy != EofPolicy::Error {
+        writer.line("return Ok(());");
+    }
+    writer.dedent();
+    writer.line("};");
+
+    let largest_input = match config.io_mode {
+        IoMode::Byte => 0xff,
+        IoMode::Unicode => char::MAX as Int,
+    };
+    if config.cell_type.contains(largest_input) {
+        writer.line("self.set_cell(value as Cell);");
+    } else {
+        let fitting = match config.overflow_policy {
+            OverflowPolicy::Error => {
+                format!(".map_err(|_| {})?", message(ExecutionErrorKind::Overflow))
+            }
+            OverflowPolicy::Wrap => String::from(".unwrap_or(value as Cell)"),
+            OverflowPolicy::Saturate => String::from(".unwrap_or(Cell::MAX)"),
+        };
+        writer.line(&format!("self.set_cell(Cell::try_from(value){fitting});"));
+    }
+    writer.line("Ok(())");
+    writer.dedent();
+    writer.line("}");
+}
+
+fn write_command(writer: &mut SourceWriter, command: &OptimizedCommand, config: &ExecutorConfig) {
+    let wrapping = config.overflow_policy == OverflowPolicy::Wrap;
+
+    match command {
+        OptimizedCommand::Add(delta) => {
+            // Cells wrap around modulo their width, so the delta can be wrapped the same way.
+            let delta = if wrapping {
+                config.cell_type.wrap(*delta)
+            } else {
+                *delta
+            };
+            writer.line(&format!("m.add({delta})?;"));
+        }
+        OptimizedCommand::Move(offset) => writer.line(&format!("m.move_pointer({offset})?;")),
+        OptimizedCommand::Clear => writer.line("m.set_cell(0);"),
+        OptimizedCommand::Scan(offset) => {
+            writer.line("while m.cell() != 0 {");
+            writer.line(&format!("    m.move_pointer({offset})?;"));
+            writer.line("}");
+        }
+        OptimizedCommand::Multiply(factors) => {
+            writer.line("if m.cell() != 0 {");
+            writer.indent();
+            writer.line("let value = m.cell();");
+            for (offset, factor) in factors {
+                writer.line(&format!("m.move_pointer({offset})?;"));
+                writer.line(&format!(
+                    "m.add(value.wrapping_mul({}))?;",
+                    config.cell_type.wrap(*factor)
+                ));
+                writer.line(&format!("m.move_pointer({})?;", -offset));
+            }
+            writer.line("m.set_cell(0);");
+            writer.dedent();
+            writer.line("}");
+        }
+        OptimizedCommand::Output => writer.line("m.output()?;"),
+        OptimizedCommand::Input => writer.line("m.input()?;"),
+        OptimizedCommand::JumpForward(_) => {
+            writer.line("while m.cell() != 0 {");
+            writer.indent();
+        }
+        OptimizedCommand::JumpBack(_) => {
+            writer.dedent();
+            writer.line("}");
+        }
+    }
+}
+
+fn message(error: ExecutionErrorKind) -> String {
+    format!("{:?}", error.to_string())
+}
@@ -0,0 +1,44 @@
+const INDENT: &str = "    ";
+
+/// Collects generated source code line by line, keeping track of the indentation.
+#[derive(Default)]
+pub struct SourceWriter {
+    code: String,
+    depth: usize,
+}
+
+impl SourceWriter {
+    pub fn new() -> SourceWriter {
+        SourceWriter::default()
+    }
+
+    /// Appends a line at the current indentation. An empty line is not indented.
+    pub fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            for _ in 0..self.depth {
+                self.code.push_str(INDENT);
+            }
+            self.code.push_str(line);
+        }
+        self.code.push('\n');
+    }
+
+    /// Appends a block of lines, indenting every one of them.
+    pub fn lines(&mut self, lines: &str) {
+        for line in lines.lines() {
+            self.line(line);
+        }
+    }
+
+    pub fn indent(&mut self) {
+        self.depth += 1;
+    }
+
+    pub fn dedent(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    pub fn finish(self) -> String {
+        self.code
+    }
+}
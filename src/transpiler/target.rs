@@ -0,0 +1,19 @@
+use clap::ValueEnum;
+use derive_more::Display;
+
+/// The language a program is translated into.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, ValueEnum)]
+pub enum Target {
+    /// A standalone C program.
+    #[display("c")]
+    C,
+    /// A standalone Rust program.
+    #[display("rust")]
+    Rust,
+    /// A WebAssembly module in the binary format.
+    #[display("wasm")]
+    Wasm,
+    /// A WebAssembly module in the text format.
+    #[display("wat")]
+    Wat,
+}
@@ -0,0 +1,43 @@
+use std::fmt;
+
+use crate::source_position::SourcePosition;
+
+/// A bracket of a program that has no matching bracket.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnmatchedBracket {
+    /// Either `'['` for a loop that is never closed, or `']'` for one that is never opened.
+    pub bracket: char,
+    pub position: SourcePosition,
+    /// The line of source code the bracket is on.
+    pub line: String,
+}
+
+impl fmt::Display for UnmatchedBracket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let SourcePosition { line, column } = self.position;
+
+        if self.bracket == '[' {
+            writeln!(
+                f,
+                "The '[' at line {line}, column {column} is never closed:"
+            )?;
+        } else {
+            writeln!(
+                f,
+                "The ']' at line {line}, column {column} has no matching '[':"
+            )?;
+        }
+
+        // Tabs are kept in front of the caret, so that it lines up with the bracket.
+        let indent: String = self
+            .line
+            .chars()
+            .take(column - 1)
+            .map(|current_char| if current_char == '\t' { '\t' } else { ' ' })
+            .collect();
+        let gutter = " ".repeat(line.to_string().len());
+
+        writeln!(f, "{line} | {}", self.line)?;
+        write!(f, "{gutter} | {indent}^")
+    }
+}
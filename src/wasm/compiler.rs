@@ -0,0 +1,397 @@
+use crate::executor::{
+    cell_type::CellType, eof_policy::EofPolicy, execution_error_kind::ExecutionErrorKind,
+    executor_config::ExecutorConfig, io_mode::IoMode, optimized_command::OptimizedCommand,
+    overflow_policy::OverflowPolicy, tape::TapeMode,
+};
+
+use super::{
+    instruction::Instruction,
+    wasm_error::WasmError,
+    wasm_module::{Function, FunctionType, Import, ValueType, WasmModule},
+};
+
+// The module imports two functions from the host and exports `run` together with its memory,
+// which holds the tape starting at address 0.
+
+/// The module the imported functions come from.
+pub const IMPORT_MODULE: &str = "bf";
+/// `input() -> i32`, reads a byte and gives it back, or gives back -1 once the input has run out.
+pub const INPUT_FUNCTION: &str = "input";
+/// `output(byte: i32)`, writes the low 8 bits of the value.
+pub const OUTPUT_FUNCTION: &str = "output";
+/// `run() -> i32`, runs the program and gives back one of the statuses below.
+pub const RUN_FUNCTION: &str = "run";
+
+/// The status `run` gives back when the program finished.
+pub const STATUS_FINISHED: i32 = 0;
+/// The status `run` gives back when the data pointer went too far to the left.
+pub const STATUS_TOO_FAR_LEFT: i32 = 1;
+/// The status `run` gives back when the data pointer went too far to the right.
+pub const STATUS_TOO_FAR_RIGHT: i32 = 2;
+/// The status `run` gives back when the input ran out and the EOF policy is `error`.
+pub const STATUS_INPUT_ERROR: i32 = 3;
+
+const PAGE_SIZE: u64 = 65536;
+
+const INPUT: u32 = 0;
+const OUTPUT: u32 = 1;
+const RESERVE: u32 = 3;
+
+// The locals of `run`.
+const POINTER: u32 = 0;
+const VALUE: u32 = 1;
+const BYTE: u32 = 2;
+
+/// The error a status given back by `run` stands for, or `None` if the program finished.
+pub fn status_error(status: i32) -> Option<ExecutionErrorKind> {
+    match status {
+        STATUS_FINISHED => None,
+        STATUS_TOO_FAR_LEFT => Some(ExecutionErrorKind::TooFarLeft),
+        STATUS_TOO_FAR_RIGHT => Some(ExecutionErrorKind::TooFarRight),
+        _ => Some(ExecutionErrorKind::InputError),
+    }
+}
+
+/// Checks whether a program with the configuration can be compiled into WebAssembly. Cells have
+/// to wrap around and fit into a WebAssembly value, the tape can not grow to the left, the I/O
+/// works on bytes, and there can be no step limit or timeout.
+pub fn check_supported(config: &ExecutorConfig) -> Result<(), WasmError> {
+    if config.cell_type == CellType::I128 {
+        return Err(WasmError::UnsupportedCellType(config.cell_type));
+    }
+
+    if config.overflow_policy != OverflowPolicy::Wrap {
+        return Err(WasmError::UnsupportedOverflowPolicy(config.overflow_policy));
+    }
+
+    if config.tape_mode == TapeMode::Bidirectional {
+        return Err(WasmError::UnsupportedTapeMode(config.tape_mode));
+    }
+
+    if config.io_mode != IoMode::Byte {
+        return Err(WasmError::UnsupportedIoMode(config.io_mode));
+    }
+
+    if config.max_steps.is_some() || config.timeout.is_some() {
+        return Err(WasmError::UnsupportedLimits);
+    }
+
+    Ok(())
+}
+
+/// Compiles an optimized program into a WebAssembly module, which behaves exactly like the
+/// program run by `ExecutorState` with the same configuration.
+pub fn compile(
+    commands: &[OptimizedCommand],
+    config: &ExecutorConfig,
+) -> Result<WasmModule, WasmError> {
+    check_supported(config)?;
+
+    let cell_size = config.cell_type.size() as u64;
+    let (tape_length, memory_pages) = match config.tape_mode {
+        TapeMode::Fixed | TapeMode::Circular => {
+            let length = config.tape_length.max(1) as u64;
+            if length * cell_size > u32::MAX as u64 + 1 {
+                return Err(WasmError::TapeTooLong);
+            }
+            (
+                length as u32,
+                (length * cell_size).div_ceil(PAGE_SIZE) as u32,
+            )
+        }
+        _ => (0, 1),
+    };
+
+    let mut compiler = Compiler {
+        body: Vec::new(),
+        cell_type: config.cell_type,
+        tape_mode: config.tape_mode,
+        tape_length,
+    };
+    for command in commands {
+        compiler.command(command, config)?;
+    }
+    compiler.body.push(Instruction::I32Const(STATUS_FINISHED));
+
+    let value_type = compiler.value_type();
+    let mut functions = vec![Function {
+        type_index: 0,
+        export: Some(RUN_FUNCTION),
+        locals: vec![ValueType::I32, value_type, ValueType::I32],
+        body: compiler.body,
+    }];
+    if config.tape_mode == TapeMode::Growable {
+        functions.push(reserve_function(cell_size as i32));
+    }
+
+    Ok(WasmModule {
+        types: vec![
+            FunctionType {
+                params: vec![],
+                results: vec![ValueType::I32],
+            },
+            FunctionType {
+                params: vec![ValueType::I32],
+                results: vec![],
+            },
+        ],
+        imports: vec![
+            Import {
+                module: IMPORT_MODULE,
+                name: INPUT_FUNCTION,
+                type_index: 0,
+            },
+            Import {
+                module: IMPORT_MODULE,
+                name: OUTPUT_FUNCTION,
+                type_index: 1,
+            },
+        ],
+        functions,
+        memory_pages,
+    })
+}
+
+/// A function `reserve(index: i32)`, which grows the memory until it holds the cell at the index.
+fn reserve_function(cell_size: i32) -> Function {
+    use Instruction::*;
+
+    let mut body = vec![Block, Loop, LocalGet(0), I32Const(1), I32Add];
+    if cell_size > 1 {
+        body.extend([I32Const(cell_size), I32Mul]);
+    }
+    body.extend([
+        MemorySize,
+        I32Const(16),
+        I32Shl,
+        I32LeU,
+        BrIf(1),
+        I32Const(1),
+        MemoryGrow,
+        I32Const(-1),
+        I32Eq,
+        If,
+        Unreachable,
+        End,
+        Br(0),
+        End,
+        End,
+    ]);
+
+    Function {
+        type_index: 1,
+        export: None,
+        locals: vec![],
+        body,
+    }
+}
+
+struct Compiler {
+    body: Vec<Instruction>,
+    cell_type: CellType,
+    tape_mode: TapeMode,
+    tape_length: u32,
+}
+
+impl Compiler {
+    fn command(
+        &mut self,
+        command: &OptimizedCommand,
+        config: &ExecutorConfig,
+    ) -> Result<(), WasmError> {
+        use Instruction::*;
+
+        match command {
+            OptimizedCommand::Add(delta) => {
+                self.address();
+                self.load_cell();
+                self.constant(config.cell_type.wrap(*delta) as i64);
+                self.body.push(self.pick(I32Add, I64Add));
+                self.store();
+            }
+            OptimizedCommand::Move(offset) => self.move_pointer(*offset)?,
+            OptimizedCommand::Clear => self.clear_cell(),
+            OptimizedCommand::Scan(offset) => {
+                self.body.extend([Block, Loop]);
+                self.load_cell();
+                self.body.extend([self.pick(I32Eqz, I64Eqz), BrIf(1)]);
+                self.move_pointer(*offset)?;
+                self.body.extend([Br(0), End, End]);
+            }
+            OptimizedCommand::Multiply(factors) => {
+                self.body.push(Block);
+                self.load_cell();
+                self.body
+                    .extend([LocalTee(VALUE), self.pick(I32Eqz, I64Eqz), BrIf(0)]);
+                for (offset, factor) in factors {
+                    self.move_pointer(*offset)?;
+                    self.address();
+                    self.load_cell();
+                    self.body.push(LocalGet(VALUE));
+                    self.constant(config.cell_type.wrap(*factor) as i64);
+                    self.body
+                        .extend([self.pick(I32Mul, I64Mul), self.pick(I32Add, I64Add)]);
+                    self.store();
+                    self.move_pointer(-offset)?;
+                }
+                self.clear_cell();
+                self.body.push(End);
+            }
+            OptimizedCommand::Output => {
+                self.load_cell();
+                if self.cell_type == CellType::I64 {
+                    self.body.push(I32WrapI64);
+                }
+                self.body.push(Call(OUTPUT));
+            }
+            OptimizedCommand::Input => {
+                self.body
+                    .extend([Call(INPUT), LocalTee(BYTE), I32Const(0), I32LtS, If]);
+                match config.eof_policy {
+                    EofPolicy::Zero => self.clear_cell(),
+                    EofPolicy::MinusOne => {
+                        self.address();
+                        self.constant(-1);
+                        self.store();
+                    }
+                    EofPolicy::Unchanged => {}
+                    EofPolicy::Error => self.body.extend([I32Const(STATUS_INPUT_ERROR), Return]),
+                }
+                self.body.push(Else);
+                self.address();
+                self.body.push(LocalGet(BYTE));
+                if self.cell_type == CellType::I64 {
+                    self.body.push(I64ExtendI32U);
+                }
+                self.store();
+                self.body.push(End);
+            }
+            OptimizedCommand::JumpForward(_) => {
+                self.body.extend([Block, Loop]);
+                self.load_cell();
+                self.body.extend([self.pick(I32Eqz, I64Eqz), BrIf(1)]);
+            }
+            OptimizedCommand::JumpBack(_) => self.body.extend([Br(0), End, End]),
+        }
+
+        Ok(())
+    }
+
+    /// Moves the data pointer by the offset, growing the memory or wrapping around the tape if
+    /// needed.
+    fn move_pointer(&mut self, offset: isize) -> Result<(), WasmError> {
+        use Instruction::*;
+
+        let offset = i32::try_from(offset).map_err(|_| WasmError::OffsetTooLarge)?;
+
+        if self.tape_mode == TapeMode::Circular {
+            let steps = offset.rem_euclid(self.tape_length as i32) as u32;
+            if steps != 0 {
+                self.body.extend([
+                    LocalGet(POINTER),
+                    I32Const(steps as i32),
+                    I32Add,
+                    I32Const(self.tape_length as i32),
+                    I32RemU,
+                    LocalSet(POINTER),
+                ]);
+            }
+            return Ok(());
+        }
+
+        self.body.extend([
+            LocalGet(POINTER),
+            I32Const(offset),
+            I32Add,
+            LocalSet(POINTER),
+        ]);
+
+        if offset < 0 {
+            self.body.extend([
+                LocalGet(POINTER),
+                I32Const(0),
+                I32LtS,
+                If,
+                I32Const(STATUS_TOO_FAR_LEFT),
+                Return,
+                End,
+            ]);
+        } else if self.tape_mode == TapeMode::Growable {
+            self.body.extend([LocalGet(POINTER), Call(RESERVE)]);
+        } else {
+            // The pointer is compared as an unsigned number, since it can only have gone past
+            // `i32::MAX` to the right.
+            self.body.extend([
+                I32Const(self.tape_length as i32),
+                LocalGet(POINTER),
+                I32LeU,
+                If,
+                I32Const(STATUS_TOO_FAR_RIGHT),
+                Return,
+                End,
+            ]);
+        }
+
+        Ok(())
+    }
+
+    /// Pushes the address of the current cell.
+    fn address(&mut self) {
+        self.body.push(Instruction::LocalGet(POINTER));
+
+        let shift = self.cell_type.size().trailing_zeros() as i32;
+        if shift > 0 {
+            self.body
+                .extend([Instruction::I32Const(shift), Instruction::I32Shl]);
+        }
+    }
+
+    /// Pushes the value of the current cell.
+    fn load_cell(&mut self) {
+        self.address();
+        self.body.push(match self.cell_type {
+            CellType::U8 => Instruction::I32Load8U,
+            CellType::U16 => Instruction::I32Load16U,
+            CellType::I64 => Instruction::I64Load,
+            _ => Instruction::I32Load,
+        });
+    }
+
+    /// Stores the value on top of the stack at the address below it. Stores keep only the low
+    /// bits of the value, which makes the cell wrap around.
+    fn store(&mut self) {
+        self.body.push(match self.cell_type {
+            CellType::U8 => Instruction::I32Store8,
+            CellType::U16 => Instruction::I32Store16,
+            CellType::I64 => Instruction::I64Store,
+            _ => Instruction::I32Store,
+        });
+    }
+
+    fn clear_cell(&mut self) {
+        self.address();
+        self.constant(0);
+        self.store();
+    }
+
+    /// Pushes a constant of the type cells are computed in, truncated to its width.
+    fn constant(&mut self, value: i64) {
+        self.body.push(self.pick(
+            Instruction::I32Const(value as i32),
+            Instruction::I64Const(value),
+        ));
+    }
+
+    fn value_type(&self) -> ValueType {
+        self.pick(ValueType::I32, ValueType::I64)
+    }
+
+    /// Picks the 32-bit or the 64-bit variant depending on the type of the cells.
+    fn pick<T>(&self, narrow: T, wide: T) -> T {
+        if self.cell_type == CellType::I64 {
+            wide
+        } else {
+            narrow
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use derive_more::Display;
+
+/// A WebAssembly instruction, written in the text format by `Display` and in the binary format
+/// by `encode`. Blocks never take parameters or give back results, and memory accesses use no
+/// offset and the natural alignment.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum Instruction {
+    #[display("unreachable")]
+    Unreachable,
+    #[display("block")]
+    Block,
+    #[display("loop")]
+    Loop,
+    #[display("if")]
+    If,
+    #[display("else")]
+    Else,
+    #[display("end")]
+    End,
+    #[display("br {}", _0)]
+    Br(u32),
+    #[display("br_if {}", _0)]
+    BrIf(u32),
+    #[display("return")]
+    Return,
+    #[display("call {}", _0)]
+    Call(u32),
+    #[display("local.get {}", _0)]
+    LocalGet(u32),
+    #[display("local.set {}", _0)]
+    LocalSet(u32),
+    #[display("local.tee {}", _0)]
+    LocalTee(u32),
+    #[display("i32.load")]
+    I32Load,
+    #[display("i64.load")]
+    I64Load,
+    #[display("i32.load8_u")]
+    I32Load8U,
+    #[display("i32.load16_u")]
+    I32Load16U,
+    #[display("i32.store")]
+    I32Store,
+    #[display("i64.store")]
+    I64Store,
+    #[display("i32.store8")]
+    I32Store8,
+    #[display("i32.store16")]
+    I32Store16,
+    #[display("memory.size")]
+    MemorySize,
+    #[display("memory.grow")]
+    MemoryGrow,
+    #[display("i32.const {}", _0)]
+    I32Const(i32),
+    #[display("i64.const {}", _0)]
+    I64Const(i64),
+    #[display("i32.eqz")]
+    I32Eqz,
+    #[display("i32.eq")]
+    I32Eq,
+    #[display("i32.lt_s")]
+    I32LtS,
+    #[display("i32.le_u")]
+    I32LeU,
+    #[display("i32.ge_s")]
+    I32GeS,
+    #[display("i64.eqz")]
+    I64Eqz,
+    #[display("i32.add")]
+    I32Add,
+    #[display("i32.mul")]
+    I32Mul,
+    #[display("i32.rem_u")]
+    I32RemU,
+    #[display("i32.shl")]
+    I32Shl,
+    #[display("i64.add")]
+    I64Add,
+    #[display("i64.mul")]
+    I64Mul,
+    #[display("i32.wrap_i64")]
+    I32WrapI64,
+    #[display("i64.extend_i32_u")]
+    I64ExtendI32U,
+}
+
+/// The block type of a block that takes no parameters and gives back no results.
+const EMPTY_BLOCK: u8 = 0x40;
+
+impl Instruction {
+    pub fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Instruction::Unreachable => bytes.push(0x00),
+            Instruction::Block => bytes.extend([0x02, EMPTY_BLOCK]),
+            Instruction::Loop => bytes.extend([0x03, EMPTY_BLOCK]),
+            Instruction::If => bytes.extend([0x04, EMPTY_BLOCK]),
+            Instruction::Else => bytes.push(0x05),
+            Instruction::End => bytes.push(0x0b),
+            Instruction::Br(depth) => encode_with_index(bytes, 0x0c, *depth),
+            Instruction::BrIf(depth) => encode_with_index(bytes, 0x0d, *depth),
+            Instruction::Return => bytes.push(0x0f),
+            Instruction::Call(function) => encode_with_index(bytes, 0x10, *function),
+            Instruction::LocalGet(local) => encode_with_index(bytes, 0x20, *local),
+            Instruction::LocalSet(local) => encode_with_index(bytes, 0x21, *local),
+            Instruction::LocalTee(local) => encode_with_index(bytes, 0x22, *local),
+            Instruction::I32Load => encode_memory_access(bytes, 0x28, 2),
+            Instruction::I64Load => encode_memory_access(bytes, 0x29, 3),
+            Instruction::I32Load8U => encode_memory_access(bytes, 0x2d, 0),
+            Instruction::I32Load16U => encode_memory_access(bytes, 0x2f, 1),
+            Instruction::I32Store => encode_memory_access(bytes, 0x36, 2),
+            Instruction::I64Store => encode_memory_access(bytes, 0x37, 3),
+            Instruction::I32Store8 => encode_memory_access(bytes, 0x3a, 0),
+            Instruction::I32Store16 => encode_memory_access(bytes, 0x3b, 1),
+            Instruction::MemorySize => bytes.extend([0x3f, 0x00]),
+            Instruction::MemoryGrow => bytes.extend([0x40, 0x00]),
+            Instruction::I32Const(value) => {
+                bytes.push(0x41);
+                encode_signed(bytes, *value as i64);
+            }
+            Instruction::I64Const(value) => {
+                bytes.push(0x42);
+                encode_signed(bytes, *value);
+            }
+            Instruction::I32Eqz => bytes.push(0x45),
+            Instruction::I32Eq => bytes.push(0x46),
+            Instruction::I32LtS => bytes.push(0x48),
+            Instruction::I32LeU => bytes.push(0x4d),
+            Instruction::I32GeS => bytes.push(0x4e),
+            Instruction::I64Eqz => bytes.push(0x50),
+            Instruction::I32Add => bytes.push(0x6a),
+            Instruction::I32Mul => bytes.push(0x6c),
+            Instruction::I32RemU => bytes.push(0x70),
+            Instruction::I32Shl => bytes.push(0x74),
+            Instruction::I64Add => bytes.push(0x7c),
+            Instruction::I64Mul => bytes.push(0x7e),
+            Instruction::I32WrapI64 => bytes.push(0xa7),
+            Instruction::I64ExtendI32U => bytes.push(0xad),
+        }
+    }
+
+    /// The change in nesting depth the instruction causes, used to indent the text format.
+    pub fn depth_change(&self) -> isize {
+        match self {
+            Instruction::Block | Instruction::Loop | Instruction::If => 1,
+            Instruction::End => -1,
+            _ => 0,
+        }
+    }
+}
+
+/// Writes an unsigned LEB128 number.
+pub fn encode_unsigned(bytes: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Writes a signed LEB128 number.
+pub fn encode_signed(bytes: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let sign_bit_clear = byte & 0x40 == 0;
+        if (value == 0 && sign_bit_clear) || (value == -1 && !sign_bit_clear) {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn encode_with_index(bytes: &mut Vec<u8>, opcode: u8, index: u32) {
+    bytes.push(opcode);
+    encode_unsigned(bytes, index as u64);
+}
+
+fn encode_memory_access(bytes: &mut Vec<u8>, opcode: u8, alignment: u32) {
+    bytes.push(opcode);
+    encode_unsigned(bytes, alignment as u64);
+    encode_unsigned(bytes, 0); // the offset
+}
@@ -0,0 +1,4 @@
+pub mod compiler;
+pub mod instruction;
+pub mod wasm_error;
+pub mod wasm_module;
@@ -0,0 +1,29 @@
+use derive_more::Display;
+
+use crate::executor::{
+    cell_type::CellType, io_mode::IoMode, overflow_policy::OverflowPolicy, tape::TapeMode,
+};
+
+#[derive(Debug, Display)]
+pub enum WasmError {
+    #[display("WebAssembly Error: Cells of type {} can not be compiled!", _0)]
+    UnsupportedCellType(CellType),
+    #[display(
+        "WebAssembly Error: Only wrapping cells can be compiled, not the {} overflow policy!",
+        _0
+    )]
+    UnsupportedOverflowPolicy(OverflowPolicy),
+    #[display("WebAssembly Error: A {} tape can not be compiled!", _0)]
+    UnsupportedTapeMode(TapeMode),
+    #[display("WebAssembly Error: Step limits and timeouts can not be compiled!")]
+    UnsupportedLimits,
+    #[display(
+        "WebAssembly Error: Only the byte I/O mode can be compiled, not the {} I/O mode!",
+        _0
+    )]
+    UnsupportedIoMode(IoMode),
+    #[display("WebAssembly Error: The tape does not fit into the linear memory!")]
+    TapeTooLong,
+    #[display("WebAssembly Error: The data pointer moves too far at once!")]
+    OffsetTooLarge,
+}
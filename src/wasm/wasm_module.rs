@@ -0,0 +1,257 @@
+use derive_more::Display;
+
+use super::instruction::{Instruction, encode_unsigned};
+
+const MAGIC: [u8; 4] = [0x00, 0x61, 0x73, 0x6d];
+const VERSION: [u8; 4] = [0x01, 0x00, 0x00, 0x00];
+
+const TYPE_SECTION: u8 = 1;
+const IMPORT_SECTION: u8 = 2;
+const FUNCTION_SECTION: u8 = 3;
+const MEMORY_SECTION: u8 = 5;
+const EXPORT_SECTION: u8 = 7;
+const CODE_SECTION: u8 = 10;
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum ValueType {
+    #[display("i32")]
+    I32,
+    #[display("i64")]
+    I64,
+}
+
+impl ValueType {
+    fn encode(&self) -> u8 {
+        match self {
+            ValueType::I32 => 0x7f,
+            ValueType::I64 => 0x7e,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionType {
+    pub params: Vec<ValueType>,
+    pub results: Vec<ValueType>,
+}
+
+/// A function provided by the host, which comes before the functions of the module when they are
+/// numbered.
+#[derive(Clone, Debug)]
+pub struct Import {
+    pub module: &'static str,
+    pub name: &'static str,
+    pub type_index: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Function {
+    pub type_index: u32,
+    pub export: Option<&'static str>,
+    pub locals: Vec<ValueType>,
+    /// The instructions of the function, without the `end` closing it.
+    pub body: Vec<Instruction>,
+}
+
+/// A WebAssembly module with a single linear memory, which is exported as `memory`.
+#[derive(Clone, Debug)]
+pub struct WasmModule {
+    pub types: Vec<FunctionType>,
+    pub imports: Vec<Import>,
+    pub functions: Vec<Function>,
+    /// The number of 64 KiB pages the memory starts with.
+    pub memory_pages: u32,
+}
+
+impl WasmModule {
+    /// Writes the module in the binary format.
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(MAGIC);
+        bytes.extend(VERSION);
+
+        write_section(&mut bytes, TYPE_SECTION, self.types.len(), |section| {
+            for function_type in &self.types {
+                section.push(0x60);
+                write_value_types(section, &function_type.params);
+                write_value_types(section, &function_type.results);
+            }
+        });
+
+        write_section(&mut bytes, IMPORT_SECTION, self.imports.len(), |section| {
+            for import in &self.imports {
+                write_name(section, import.module);
+                write_name(section, import.name);
+                section.push(0x00); // a function
+                encode_unsigned(section, import.type_index as u64);
+            }
+        });
+
+        write_section(
+            &mut bytes,
+            FUNCTION_SECTION,
+            self.functions.len(),
+            |section| {
+                for function in &self.functions {
+                    encode_unsigned(section, function.type_index as u64);
+                }
+            },
+        );
+
+        write_section(&mut bytes, MEMORY_SECTION, 1, |section| {
+            section.push(0x00); // no maximum
+            encode_unsigned(section, self.memory_pages as u64);
+        });
+
+        let exports = self.exports();
+        write_section(&mut bytes, EXPORT_SECTION, exports.len() + 1, |section| {
+            for (name, index) in &exports {
+                write_name(section, name);
+                section.push(0x00); // a function
+                encode_unsigned(section, *index as u64);
+            }
+            write_name(section, "memory");
+            section.push(0x02); // a memory
+            encode_unsigned(section, 0);
+        });
+
+        write_section(&mut bytes, CODE_SECTION, self.functions.len(), |section| {
+            for function in &self.functions {
+                let mut code = Vec::new();
+
+                let groups = group_locals(&function.locals);
+                encode_unsigned(&mut code, groups.len() as u64);
+                for (count, value_type) in groups {
+                    encode_unsigned(&mut code, count as u64);
+                    code.push(value_type.encode());
+                }
+                for instruction in &function.body {
+                    instruction.encode(&mut code);
+                }
+                Instruction::End.encode(&mut code);
+
+                encode_unsigned(section, code.len() as u64);
+                section.extend(code);
+            }
+        });
+
+        bytes
+    }
+
+    /// Writes the module in the text format.
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![String::from("(module")];
+
+        for (index, function_type) in self.types.iter().enumerate() {
+            let mut signature = String::from("func");
+            write_value_types_text(&mut signature, "param", &function_type.params);
+            write_value_types_text(&mut signature, "result", &function_type.results);
+            lines.push(format!("  (type (;{index};) ({signature}))"));
+        }
+
+        for (index, import) in self.imports.iter().enumerate() {
+            lines.push(format!(
+                "  (import {:?} {:?} (func (;{index};) (type {})))",
+                import.module, import.name, import.type_index
+            ));
+        }
+
+        lines.push(format!("  (memory (;0;) {})", self.memory_pages));
+
+        for (name, index) in self.exports() {
+            lines.push(format!("  (export {name:?} (func {index}))"));
+        }
+        lines.push(String::from("  (export \"memory\" (memory 0))"));
+
+        for (index, function) in self.functions.iter().enumerate() {
+            let mut header = format!(
+                "  (func (;{};) (type {})",
+                index + self.imports.len(),
+                function.type_index
+            );
+            write_value_types_text(&mut header, "local", &function.locals);
+            lines.push(header);
+
+            let mut depth = 2;
+            for instruction in &function.body {
+                let change = instruction.depth_change();
+                if change < 0 || *instruction == Instruction::Else {
+                    depth -= 1;
+                }
+                lines.push(format!("{}{}", "  ".repeat(depth), instruction));
+                if change > 0 || *instruction == Instruction::Else {
+                    depth += 1;
+                }
+            }
+
+            lines.push(String::from("  )"));
+        }
+
+        lines.push(String::from(")"));
+        lines.join("\n") + "\n"
+    }
+
+    /// The names and indices of the exported functions.
+    fn exports(&self) -> Vec<(&'static str, usize)> {
+        self.functions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, function)| {
+                function
+                    .export
+                    .map(|name| (name, index + self.imports.len()))
+            })
+            .collect()
+    }
+}
+
+fn write_section(
+    bytes: &mut Vec<u8>,
+    id: u8,
+    count: usize,
+    write_entries: impl FnOnce(&mut Vec<u8>),
+) {
+    let mut section = Vec::new();
+    encode_unsigned(&mut section, count as u64);
+    write_entries(&mut section);
+
+    bytes.push(id);
+    encode_unsigned(bytes, section.len() as u64);
+    bytes.extend(section);
+}
+
+fn write_name(bytes: &mut Vec<u8>, name: &str) {
+    encode_unsigned(bytes, name.len() as u64);
+    bytes.extend(name.as_bytes());
+}
+
+fn write_value_types(bytes: &mut Vec<u8>, value_types: &[ValueType]) {
+    encode_unsigned(bytes, value_types.len() as u64);
+    bytes.extend(value_types.iter().map(ValueType::encode));
+}
+
+fn write_value_types_text(text: &mut String, keyword: &str, value_types: &[ValueType]) {
+    if value_types.is_empty() {
+        return;
+    }
+
+    text.push_str(&format!(" ({keyword}"));
+    for value_type in value_types {
+        text.push_str(&format!(" {value_type}"));
+    }
+    text.push(')');
+}
+
+/// Groups runs of locals of the same type, which is how the binary format declares them.
+fn group_locals(locals: &[ValueType]) -> Vec<(u32, ValueType)> {
+    let mut groups: Vec<(u32, ValueType)> = Vec::new();
+
+    for local in locals {
+        match groups.last_mut() {
+            Some((count, value_type)) if value_type == local => *count += 1,
+            _ => groups.push((1, *local)),
+        }
+    }
+
+    groups
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "async")]
+
+use std::time::Duration;
+
+use bfdbg::{
+    executor::{
+        async_executor::AsyncExecutor, cell_type::CellType, eof_policy::EofPolicy,
+        execution_error_kind::ExecutionErrorKind, executor_config::ExecutorConfig, io_mode::IoMode,
+    },
+    parsing_src::translate_into_commands,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+
+const HELLO_WORLD: &str = "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.";
+
+fn byte_config() -> ExecutorConfig {
+    ExecutorConfig {
+        cell_type: CellType::U8,
+        eof_policy: EofPolicy::Zero,
+        io_mode: IoMode::Byte,
+        ..ExecutorConfig::default()
+    }
+}
+
+fn executor<R, W>(source: &str, input: R, output: W) -> AsyncExecutor<R, W>
+where
+    R: tokio::io::AsyncRead + Unpin,
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    let (commands, _) = translate_into_commands(source, false).unwrap();
+    AsyncExecutor::new(commands, byte_config(), input, output)
+}
+
+#[tokio::test]
+async fn output_streams_over_a_duplex() {
+    // The pipe holds fewer bytes than the program writes, so it has to be read while it runs.
+    let (writer, mut reader) = tokio::io::duplex(4);
+    let mut executor = executor(HELLO_WORLD, &b""[..], writer);
+
+    let mut output = Vec::new();
+    let (result, read) = tokio::join!(
+        async {
+            let result = executor.run().await;
+            drop(executor);
+            result
+        },
+        reader.read_to_end(&mut output)
+    );
+
+    assert_eq!(result, Ok(()));
+    read.unwrap();
+    assert_eq!(output, b"Hello World!\n");
+}
+
+#[tokio::test]
+async fn input_streams_over_a_duplex() {
+    let (mut writer, reader) = tokio::io::duplex(4);
+    let mut executor = executor(",[.,]", reader, Vec::new());
+
+    let (result, written) = tokio::join!(executor.run(), async {
+        writer.write_all(b"streamed input").await?;
+        writer.shutdown().await
+    });
+
+    assert_eq!(result, Ok(()));
+    written.unwrap();
+    assert_eq!(executor.into_io().1, b"streamed input");
+}
+
+#[tokio::test]
+async fn endless_programs_can_be_cancelled() {
+    let mut executor = executor("+[]", &b""[..], Vec::new());
+
+    // The timer only fires if the program yields to the runtime while it runs.
+    let cancelled = tokio::time::timeout(Duration::from_millis(50), executor.run()).await;
+
+    assert!(cancelled.is_err());
+    assert!(executor.get_executor().get_state().get_steps() > 0);
+}
+
+#[tokio::test]
+async fn output_is_flushed_when_the_program_fails() {
+    let buffered = BufWriter::new(Vec::new());
+    let mut executor = executor("++++++++[>++++++++<-]>+.+.<<", &b""[..], buffered);
+
+    let result = executor.run().await;
+
+    assert_eq!(
+        result.map_err(|err| err.get_kind()),
+        Err(ExecutionErrorKind::TooFarLeft)
+    );
+    assert_eq!(executor.into_io().1.into_inner(), b"AB");
+}
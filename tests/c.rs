@@ -0,0 +1,231 @@
+mod common;
+
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use bfdbg::{
+    executor::{
+        cell_type::CellType, eof_policy::EofPolicy, executor_config::ExecutorConfig,
+        io_mode::IoMode, overflow_policy::OverflowPolicy, tape::TapeMode,
+    },
+    parsing_src::translate_into_commands,
+    transpiler::c_code::transpile,
+};
+use common::{CELL_TYPES, PROGRAMS, TAPES, XorShift, interpret};
+
+const OVERFLOW_POLICIES: [OverflowPolicy; 3] = [
+    OverflowPolicy::Wrap,
+    OverflowPolicy::Error,
+    OverflowPolicy::Saturate,
+];
+
+const TIME_LIMIT: Duration = Duration::from_secs(10);
+
+/// A compiled program, removed again once the test is done with it.
+struct Binary(PathBuf);
+
+impl Binary {
+    /// Transpiles the program and compiles it with `cc`.
+    fn compile(source: &str, config: ExecutorConfig) -> Binary {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let (commands, _) = translate_into_commands(source, false).unwrap();
+        let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!(
+            "bfdbg-c-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let mut cc = Command::new("cc")
+            .args(["-O0", "-w", "-x", "c", "-", "-o"])
+            .arg(&path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .unwrap();
+        cc.stdin
+            .take()
+            .unwrap()
+            .write_all(transpile(&commands, &config).as_bytes())
+            .unwrap();
+        assert!(cc.wait().unwrap().success(), "{} with {:?}", source, config);
+
+        Binary(path)
+    }
+
+    /// Runs the program, giving back its output and the error it failed with, or `None` if it
+    /// had to be killed after running for longer than `TIME_LIMIT`.
+    fn run(&self, input: &[u8]) -> Option<(Vec<u8>, Result<(), String>)> {
+        let stdout = self.0.with_extension("out");
+        let stderr = self.0.with_extension("err");
+        let mut child = Command::new(&self.0)
+            .stdin(Stdio::piped())
+            .stdout(File::create(&stdout).unwrap())
+            .stderr(File::create(&stderr).unwrap())
+            .spawn()
+            .unwrap();
+        // The program may fail before reading all of its input.
+        let _ = child.stdin.take().unwrap().write_all(input);
+
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait().unwrap() {
+                break Some(status);
+            }
+            if started.elapsed() > TIME_LIMIT {
+                child.kill().unwrap();
+                child.wait().unwrap();
+                break None;
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+
+        let output = std::fs::read(&stdout).unwrap();
+        let errors = std::fs::read(&stderr).unwrap();
+        let _ = std::fs::remove_file(stdout);
+        let _ = std::fs::remove_file(stderr);
+
+        let result = if status?.success() {
+            Ok(())
+        } else {
+            assert_eq!(status?.code(), Some(1));
+            Err(String::from_utf8(errors).unwrap())
+        };
+        Some((output, result))
+    }
+}
+
+impl Drop for Binary {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn has_compiler() -> bool {
+    let found = Command::new("cc")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .status()
+        .is_ok();
+    if !found {
+        eprintln!("cc was not found, skipping the C tests");
+    }
+    found
+}
+
+/// Compiles the program once and compares it with the interpreter on every input.
+fn assert_same_behaviour(source: &str, config: ExecutorConfig, inputs: &[&[u8]]) {
+    let expected: Vec<_> = inputs
+        .iter()
+        .filter_map(|input| Some((*input, interpret(source, config, input)?)))
+        .collect();
+    if expected.is_empty() {
+        return;
+    }
+
+    let binary = Binary::compile(source, config);
+    for (input, expected) in expected {
+        let expected = Some((
+            expected.output,
+            expected.result.map_err(|kind| format!("{}\n", kind)),
+        ));
+        assert_eq!(
+            binary.run(input),
+            expected,
+            "{} with {:?} on {:?}",
+            source,
+            config,
+            input
+        );
+    }
+}
+
+#[test]
+fn programs_behave_like_the_interpreter() {
+    if !has_compiler() {
+        return;
+    }
+
+    let cell_types: Vec<_> = CELL_TYPES.into_iter().chain([CellType::I128]).collect();
+    let tapes = TAPES.into_iter().chain([(TapeMode::Bidirectional, 1)]);
+
+    for (index, program) in PROGRAMS.iter().enumerate() {
+        for (tape_mode, tape_length) in tapes.clone() {
+            let config = ExecutorConfig {
+                cell_type: cell_types[index % cell_types.len()],
+                overflow_policy: OVERFLOW_POLICIES[index % OVERFLOW_POLICIES.len()],
+                tape_mode,
+                tape_length,
+                io_mode: IoMode::Byte,
+                ..ExecutorConfig::default()
+            };
+            assert_same_behaviour(program, config, &[b""]);
+        }
+    }
+}
+
+#[test]
+fn input_behaves_like_the_interpreter() {
+    if !has_compiler() {
+        return;
+    }
+
+    let inputs: [&[u8]; 5] = [
+        b"",
+        b"a",
+        b"hello\n",
+        "héllo ✓\n".as_bytes(),
+        &[0, 1, 127, 128, 255],
+    ];
+    let programs = [",[.,]", ",.,.,.,.,.", ",>,>,<<.>.>.", ",[->+<]>."];
+
+    for io_mode in [IoMode::Byte, IoMode::Unicode] {
+        for eof_policy in [
+            EofPolicy::Zero,
+            EofPolicy::MinusOne,
+            EofPolicy::Unchanged,
+            EofPolicy::Error,
+        ] {
+            let config = ExecutorConfig {
+                cell_type: CellType::U16,
+                eof_policy,
+                io_mode,
+                ..ExecutorConfig::default()
+            };
+            for program in programs {
+                assert_same_behaviour(program, config, &inputs);
+            }
+        }
+    }
+}
+
+#[test]
+fn random_programs_behave_like_the_interpreter() {
+    if !has_compiler() {
+        return;
+    }
+
+    let mut random = XorShift(0x2545f4914f6cdd1d);
+
+    for _ in 0..60 {
+        let program = random.program(40);
+        let input = random.bytes(4);
+        let (tape_mode, tape_length) = TAPES[random.below(TAPES.len())];
+        let config = ExecutorConfig {
+            cell_type: CELL_TYPES[random.below(CELL_TYPES.len())],
+            overflow_policy: OVERFLOW_POLICIES[random.below(OVERFLOW_POLICIES.len())],
+            tape_mode,
+            tape_length,
+            eof_policy: EofPolicy::Zero,
+            io_mode: IoMode::Byte,
+            ..ExecutorConfig::default()
+        };
+        assert_same_behaviour(&program, config, &[&input]);
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+/// A file in the temporary directory, removed again once the test is done with it.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, contents: &str) -> TempFile {
+        let path = std::env::temp_dir().join(format!("bfdbg-cli-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        TempFile(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn bfdbg(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bfdbg"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn missing_sources_are_reported_on_the_standard_error() {
+    for args in [
+        vec!["/nonexistent/bfdbg/program.bf"],
+        vec!["--debug", "/nonexistent/bfdbg/program.bf"],
+        vec!["compile", "--target", "c", "/nonexistent/bfdbg/program.bf"],
+    ] {
+        let output = bfdbg(&args);
+        assert!(!output.status.success(), "{:?}", args);
+        assert_ne!(output.status.code(), Some(101), "{:?}", args);
+        assert!(output.stdout.is_empty(), "{:?}", args);
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("IO Error"),
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn debugger_parse_errors_are_reported_on_the_standard_error() {
+    let source = TempFile::new("debug-parse-error.bf", "+[");
+
+    let output = bfdbg(&["--debug", source.path()]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Syntax Error"));
+}
+
+#[test]
+fn oversized_tapes_are_rejected() {
+    let source = TempFile::new("oversized-tape.bf", "+.");
+
+    for tape in ["fixed", "circular"] {
+        let output = bfdbg(&[
+            source.path(),
+            "-t",
+            tape,
+            "--tape-length",
+            "18446744073709551615",
+        ]);
+        assert!(!output.status.success(), "{}", tape);
+        assert_ne!(output.status.code(), Some(101), "{}", tape);
+        assert!(output.stdout.is_empty(), "{}", tape);
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("--tape-length"),
+            "{}",
+            tape
+        );
+    }
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn build_runs_with_the_default_options() {
+    let source = TempFile::new("build-default.bf", "++++++++[>++++++<-]>+.+.");
+    let executable = TempFile::new("build-default", "");
+
+    let output = bfdbg(&["build", source.path(), "-o", executable.path()]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let run = Command::new(executable.path()).output().unwrap();
+    assert!(run.status.success());
+    assert_eq!(run.stdout, b"12");
+}
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[test]
+fn built_executables_report_errors_on_the_standard_error() {
+    let source = TempFile::new(
+        "build-error.bf",
+        "+++++++++++++++++++++++++++++++++++++++++++++++++.<",
+    );
+    let executable = TempFile::new("build-error", "");
+
+    assert!(
+        bfdbg(&["build", source.path(), "-o", executable.path()])
+            .status
+            .success()
+    );
+
+    let run = Command::new(executable.path()).output().unwrap();
+    assert_eq!(run.status.code(), Some(1));
+    assert_eq!(run.stdout, b"1");
+    assert_eq!(
+        String::from_utf8_lossy(&run.stderr),
+        "Index Error: You have gone too far to the left!\n"
+    );
+}
+
+#[test]
+fn build_failures_exit_with_a_failure() {
+    let source = TempFile::new("build-failure.bf", "+.");
+    let broken = TempFile::new("build-failure-broken.bf", "[");
+    let executable = TempFile::new("build-failure", "");
+
+    for args in [
+        vec![
+            "build",
+            source.path(),
+            "-c",
+            "i128",
+            "-o",
+            executable.path(),
+        ],
+        vec!["build", broken.path(), "-o", executable.path()],
+        vec![
+            "build",
+            source.path(),
+            "-o",
+            "/nonexistent/bfdbg/executable",
+        ],
+    ] {
+        let output = bfdbg(&args);
+        assert!(!output.status.success(), "{:?}", args);
+        assert!(output.stdout.is_empty(), "{:?}", args);
+        assert!(!output.stderr.is_empty(), "{:?}", args);
+    }
+}
+
+#[test]
+fn coverage_succeeds_when_every_run_succeeds() {
+    let source = TempFile::new("coverage-success.bf", ",.,.,.");
+    let input = TempFile::new("coverage-success.txt", "abc");
+    let lcov = TempFile::new("coverage-success.info", "");
+
+    let output = bfdbg(&[
+        "coverage",
+        source.path(),
+        "-i",
+        input.path(),
+        "--lcov",
+        lcov.path(),
+    ]);
+    assert!(output.status.success());
+    assert!(!output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+    assert!(!std::fs::read(lcov.path()).unwrap().is_empty());
+}
+
+#[test]
+fn coverage_failures_exit_with_a_failure() {
+    let source = TempFile::new("coverage-failure.bf", ",.,.,.<");
+    let input = TempFile::new("coverage-failure.txt", "abc");
+    let missing = "/nonexistent/bfdbg/input";
+
+    for args in [
+        vec!["coverage", source.path(), "-i", input.path()],
+        vec!["coverage", source.path(), "-i", missing],
+        vec![
+            "coverage",
+            source.path(),
+            "-i",
+            input.path(),
+            "--lcov",
+            "/nonexistent/bfdbg/coverage.info",
+        ],
+    ] {
+        let output = bfdbg(&args);
+        assert!(!output.status.success(), "{:?}", args);
+        assert!(!output.stderr.is_empty(), "{:?}", args);
+        // The coverage is still written.
+        assert!(!output.stdout.is_empty(), "{:?}", args);
+    }
+}
+
+#[test]
+fn snapshot_failures_are_reported_on_the_standard_error() {
+    let source = TempFile::new("snapshot-failure.bf", "+[>+<]");
+
+    let output = bfdbg(&[
+        source.path(),
+        "--max-steps",
+        "10",
+        "--snapshot",
+        "/nonexistent/bfdbg/snapshot.json",
+    ]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Snapshot Error"));
+}
+
+#[test]
+fn resumed_programs_report_where_they_fail() {
+    let source = TempFile::new("resume-error.bf", "+++[>+<-]\n  <");
+    let snapshot = TempFile::new("resume-error.json", "");
+
+    let output = bfdbg(&[
+        source.path(),
+        "--max-steps",
+        "10",
+        "--snapshot",
+        snapshot.path(),
+    ]);
+    assert!(!output.status.success());
+
+    let output = bfdbg(&["--resume", snapshot.path()]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("too far to the left"), "{}", stderr);
+    assert!(stderr.contains("(line 2, column 3)"), "{}", stderr);
+}
+
+#[test]
+fn compiled_rust_programs_report_errors_on_the_standard_error() {
+    let source = TempFile::new(
+        "compile-error.bf",
+        "+++++++++++++++++++++++++++++++++++++++++++++++++.<",
+    );
+    let code = TempFile::new("compile-error.rs", "");
+    let executable = TempFile::new("compile-error", "");
+
+    let output = bfdbg(&[
+        "compile",
+        source.path(),
+        "--target",
+        "rust",
+        "-c",
+        "u8",
+        "-o",
+        code.path(),
+    ]);
+    assert!(output.status.success());
+    assert!(
+        Command::new("rustc")
+            .args([code.path(), "-o", executable.path()])
+            .status()
+            .unwrap()
+            .success()
+    );
+
+    let run = Command::new(executable.path()).output().unwrap();
+    assert_eq!(run.status.code(), Some(1));
+    assert_eq!(run.stdout, b"1");
+    assert_eq!(
+        String::from_utf8_lossy(&run.stderr),
+        "Index Error: You have gone too far to the left!\n"
+    );
+}
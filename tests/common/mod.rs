@@ -0,0 +1,143 @@
+//! What the tests comparing the compiled backends with the interpreter share.
+
+use std::io::Cursor;
+
+use bfdbg::{
+    executor::{
+        cell_type::CellType, error_context::ErrorContext, executing_state::ExecutionState,
+        execution_error_kind::ExecutionErrorKind, executor_config::ExecutorConfig,
+        executor_state::ExecutorState, tape::TapeMode,
+    },
+    parsing_src::translate_into_commands,
+};
+
+/// The cell types the compiled backends support.
+pub const CELL_TYPES: [CellType; 5] = [
+    CellType::U8,
+    CellType::U16,
+    CellType::U32,
+    CellType::I32,
+    CellType::I64,
+];
+
+/// The tapes the compiled backends support, with their lengths.
+pub const TAPES: [(TapeMode, usize); 7] = [
+    (TapeMode::Growable, 1),
+    (TapeMode::Fixed, 1),
+    (TapeMode::Fixed, 4),
+    (TapeMode::Fixed, 30000),
+    (TapeMode::Circular, 1),
+    (TapeMode::Circular, 3),
+    (TapeMode::Circular, 8),
+];
+
+/// The number of steps after which the interpreter gives up on a program, which is then left out
+/// because the compiled backends would not stop.
+pub const MAX_STEPS: u64 = 20000;
+
+pub const HELLO_WORLD: &str = "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.";
+
+/// Programs built around clear, scan and multiply loops, moves past both ends of the tape and
+/// cells going past their bounds.
+pub const PROGRAMS: &[&str] = &[
+    HELLO_WORLD,
+    "+++++[-]>--[+]<.>.",
+    "++>+++<[->+<]>.",
+    "+++[->++>+++<<]>.>.",
+    "++++++++[->++++++++<]>[->++++<]>.",
+    "--[+>+>+<<]>.>.",
+    "++[->>>+<<<]>>>.",
+    "+++[-<+>]",
+    "+[->+<<+>]",
+    "+>+>+>+<<<[>]+.",
+    "+>+>+[<]+.",
+    ">>+<+<+[>>]+.",
+    "+[<<]",
+    "<",
+    ">+++++++++++<<",
+    "+[->>>>>+<<<<<]>>>>>.",
+    "+>+<[->>+<<<+>]",
+    "+>>>+<<<[>]+[<<<<<]",
+    ">>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+.",
+    "++++[->+++[->++<]<]>>.",
+    "-.",
+    "--------[->+<]>.",
+    "++++++++[>++++++++<-]>[<++++>-]+<[>-<[>++++<-]>[<++++++++>-]<[>++++++++<-]+>[>++++++++++[>+++++<-]>+.-.[-]<<[-]<->] <[>>+++++++[>+++++++<-]>.+++++.[-]<<<-]] >[>++++++++[>+++++++<-]>.[-]<<-]<+++++++++++[>+++>+++++++++>+++++++++>+<<<<-]>-.>-.+++++++.+++++++++++.<.>>.++.+++++++..<-.>>-[[-]<]",
+];
+
+#[derive(Debug, PartialEq)]
+pub struct Outcome {
+    pub output: Vec<u8>,
+    pub result: Result<(), ExecutionErrorKind>,
+    /// Where the program failed, for the backends that know it.
+    pub context: Option<ErrorContext>,
+}
+
+/// Runs the program with the interpreter, or gives back `None` if it runs for too long.
+pub fn interpret(source: &str, config: ExecutorConfig, input: &[u8]) -> Option<Outcome> {
+    let (commands, _) = translate_into_commands(source, false).unwrap();
+    let config = ExecutorConfig {
+        max_steps: Some(MAX_STEPS),
+        ..config
+    };
+    let mut state = ExecutorState::with_io(commands, config, Cursor::new(input), Vec::new());
+
+    let (result, context) = loop {
+        match state.execute_once() {
+            Ok(ExecutionState::Running) => {}
+            Ok(ExecutionState::Finished) => break (Ok(()), None),
+            Err(err) => match err.get_kind() {
+                ExecutionErrorKind::StepLimitExceeded { .. } => return None,
+                kind => break (Err(kind), err.get_context().copied()),
+            },
+        }
+    };
+
+    Some(Outcome {
+        output: state.into_io().1,
+        result,
+        context,
+    })
+}
+
+/// A small pseudo-random generator, so the programs are the same on every run.
+pub struct XorShift(pub u64);
+
+impl XorShift {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    pub fn bytes(&mut self, length: usize) -> Vec<u8> {
+        (0..self.below(length + 1))
+            .map(|_| self.next() as u8)
+            .collect()
+    }
+
+    /// A program with matching brackets, made mostly of the commands the optimizer folds.
+    pub fn program(&mut self, length: usize) -> String {
+        let mut program = String::new();
+        let mut open = 0;
+
+        for _ in 0..self.below(length + 1) {
+            let command = b"+-<>[]+-<>.,"[self.below(12)];
+            match command {
+                b'[' => open += 1,
+                b']' if open == 0 => continue,
+                b']' => open -= 1,
+                _ => {}
+            }
+            program.push(command as char);
+        }
+        program.extend(std::iter::repeat_n(']', open));
+
+        program
+    }
+}
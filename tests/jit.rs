@@ -0,0 +1,173 @@
+#![cfg(all(target_os = "linux", target_arch = "x86_64"))]
+
+mod common;
+
+use std::{io::Cursor, time::Duration};
+
+use bfdbg::{
+    executor::{
+        cell_type::CellType, eof_policy::EofPolicy, execution_error_kind::ExecutionErrorKind,
+        executor_config::ExecutorConfig, io_mode::IoMode, tape::TapeMode,
+    },
+    jit::jit_program::JitProgram,
+    parsing_src::translate_into_commands,
+};
+use common::{CELL_TYPES, Outcome, PROGRAMS, TAPES, XorShift, interpret};
+
+fn run_compiled(source: &str, config: ExecutorConfig, input: &[u8]) -> Outcome {
+    let (commands, _) = translate_into_commands(source, false).unwrap();
+    let program = JitProgram::compile(&commands, config).unwrap();
+    let mut output = Vec::new();
+    let result = program.run(Cursor::new(input), &mut output);
+
+    Outcome {
+        output,
+        result: result.map_err(|err| err.get_kind()),
+        context: result.err().and_then(|err| err.get_context().copied()),
+    }
+}
+
+fn assert_same_behaviour(source: &str, config: ExecutorConfig, input: &[u8]) {
+    if let Some(expected) = interpret(source, config, input) {
+        assert_eq!(
+            run_compiled(source, config, input),
+            expected,
+            "{} with {:?}",
+            source,
+            config
+        );
+    }
+}
+
+fn configs() -> impl Iterator<Item = ExecutorConfig> {
+    CELL_TYPES.into_iter().flat_map(|cell_type| {
+        TAPES
+            .into_iter()
+            .map(move |(tape_mode, tape_length)| ExecutorConfig {
+                cell_type,
+                tape_mode,
+                tape_length,
+                ..ExecutorConfig::default()
+            })
+    })
+}
+
+#[test]
+fn programs_behave_like_the_interpreter() {
+    for config in configs() {
+        for program in PROGRAMS {
+            assert_same_behaviour(program, config, b"");
+        }
+    }
+}
+
+#[test]
+fn input_behaves_like_the_interpreter() {
+    let inputs: [&[u8]; 4] = [b"", b"a", b"hello\n", "héllo ✓\n".as_bytes()];
+    let programs = [",[.,]", ",.,.,.,.,.", ",>,>,<<.>.>.", ",[->+<]>."];
+
+    for config in configs() {
+        for eof_policy in [
+            EofPolicy::Zero,
+            EofPolicy::MinusOne,
+            EofPolicy::Unchanged,
+            EofPolicy::Error,
+        ] {
+            for io_mode in [IoMode::Byte, IoMode::Unicode] {
+                let config = ExecutorConfig {
+                    eof_policy,
+                    io_mode,
+                    ..config
+                };
+                for program in programs {
+                    for input in inputs {
+                        assert_same_behaviour(program, config, input);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn max_cells_behaves_like_the_interpreter() {
+    let programs = [">>>>+.", "+[>+]", "+[->>>+<<<]>>>.", "+>+>+<<[>]>>>>>."];
+
+    for cell_type in CELL_TYPES {
+        for max_cells in [1, 2, 4, 5, 64] {
+            let config = ExecutorConfig {
+                cell_type,
+                max_cells: Some(max_cells),
+                ..ExecutorConfig::default()
+            };
+            for program in programs {
+                assert_same_behaviour(program, config, b"");
+            }
+        }
+    }
+}
+
+#[test]
+fn fixed_tapes_longer_than_max_cells_are_rejected() {
+    for tape_mode in [TapeMode::Fixed, TapeMode::Circular] {
+        let config = ExecutorConfig {
+            cell_type: CellType::U8,
+            tape_mode,
+            tape_length: 8,
+            max_cells: Some(4),
+            ..ExecutorConfig::default()
+        };
+        let outcome = run_compiled("+.", config, b"");
+        assert!(outcome.output.is_empty());
+        assert_eq!(
+            outcome.result,
+            Err(ExecutionErrorKind::TapeLimitExceeded { cells: 4 })
+        );
+    }
+}
+
+#[test]
+fn random_programs_behave_like_the_interpreter() {
+    let mut random = XorShift(0x2545f4914f6cdd1d);
+
+    for _ in 0..3000 {
+        let program = random.program(40);
+        let input = random.bytes(4);
+        let (tape_mode, tape_length) = TAPES[random.below(TAPES.len())];
+        let config = ExecutorConfig {
+            cell_type: CELL_TYPES[random.below(CELL_TYPES.len())],
+            tape_mode,
+            tape_length,
+            eof_policy: EofPolicy::Zero,
+            io_mode: IoMode::Byte,
+            ..ExecutorConfig::default()
+        };
+        assert_same_behaviour(&program, config, &input);
+    }
+}
+
+#[test]
+fn unsupported_configurations_are_rejected() {
+    let (commands, _) = translate_into_commands("+", false).unwrap();
+
+    for config in [
+        ExecutorConfig::default(),
+        ExecutorConfig {
+            cell_type: CellType::U8,
+            tape_mode: TapeMode::Bidirectional,
+            ..ExecutorConfig::default()
+        },
+        ExecutorConfig {
+            cell_type: CellType::U8,
+            max_steps: Some(10),
+            ..ExecutorConfig::default()
+        },
+        ExecutorConfig {
+            cell_type: CellType::U8,
+            timeout: Some(Duration::from_secs(1)),
+            ..ExecutorConfig::default()
+        },
+    ] {
+        assert!(JitProgram::compile(&commands, config).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    io::{Cursor, Read, Write},
+    process::{Command, Stdio},
+    thread,
+};
+
+use bfdbg::{
+    executor::{
+        cell_type::CellType, eof_policy::EofPolicy, executing_state::ExecutionState,
+        executor_config::ExecutorConfig, executor_state::ExecutorState, io_mode::IoMode,
+    },
+    parsing_src::translate_into_commands,
+};
+
+/// Echoes its input until it runs out.
+const ECHO: &str = ",[.,]";
+
+fn byte_config() -> ExecutorConfig {
+    ExecutorConfig {
+        cell_type: CellType::U8,
+        eof_policy: EofPolicy::Zero,
+        io_mode: IoMode::Byte,
+        ..ExecutorConfig::default()
+    }
+}
+
+/// Every byte but 0, which would stop the echo, over and over.
+fn binary_data(length: usize) -> Vec<u8> {
+    (0..length).map(|index| (index % 255 + 1) as u8).collect()
+}
+
+fn run<R: Read>(
+    source: &str,
+    config: ExecutorConfig,
+    input: R,
+) -> (ExecutorState<R, Vec<u8>>, bool) {
+    let (commands, _) = translate_into_commands(source, false).unwrap();
+    let mut state = ExecutorState::with_io(commands, config, input, Vec::new());
+
+    let finished = loop {
+        match state.execute_once() {
+            Ok(ExecutionState::Running) => {}
+            Ok(ExecutionState::Finished) => break true,
+            Err(_) => break false,
+        }
+    };
+
+    (state, finished)
+}
+
+#[test]
+fn with_io_delivers_every_byte_in_order() {
+    let data = binary_data(10000);
+
+    let (state, finished) = run(ECHO, byte_config(), Cursor::new(data.clone()));
+
+    assert!(finished);
+    assert_eq!(state.get_bytes_read(), data.len() as u64);
+    assert_eq!(state.get_output(), &data);
+}
+
+#[test]
+fn with_io_delivers_multi_byte_characters_in_order() {
+    let text = "héllo, wörld ✓ 🦀\n";
+    let config = ExecutorConfig {
+        eof_policy: EofPolicy::Zero,
+        ..ExecutorConfig::default()
+    };
+
+    let (state, finished) = run(ECHO, config, Cursor::new(text.as_bytes()));
+
+    assert!(finished);
+    assert_eq!(String::from_utf8_lossy(state.get_output()), text);
+}
+
+#[test]
+fn with_io_does_not_read_ahead() {
+    let (state, finished) = run(",>,", byte_config(), Cursor::new(b"abcdef".to_vec()));
+
+    assert!(finished);
+    assert_eq!(state.get_cell(0), Some(b'a' as i128));
+    assert_eq!(state.get_cell(1), Some(b'b' as i128));
+
+    let (mut input, _) = state.into_io();
+    let mut rest = Vec::new();
+    input.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, b"cdef");
+}
+
+#[test]
+fn piped_stdin_delivers_every_byte_in_order() {
+    // Larger than a pipe buffer, so the input arrives in several reads.
+    let data = binary_data(300000);
+    let path = std::env::temp_dir().join(format!("bfdbg-pipe-input-{}.bf", std::process::id()));
+    std::fs::write(&path, ECHO).unwrap();
+
+    for backend in [None, Some("--no-optimize")] {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_bfdbg"))
+            .arg(&path)
+            .args(["--cell-type", "u8", "--eof", "zero", "--io", "byte"])
+            .args(backend)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        let writer = {
+            let data = data.clone();
+            thread::spawn(move || stdin.write_all(&data))
+        };
+        let output = child.wait_with_output().unwrap();
+        writer.join().unwrap().unwrap();
+
+        assert!(output.status.success(), "{:?}", backend);
+        assert_eq!(output.stdout.len(), data.len(), "{:?}", backend);
+        assert!(output.stdout == data, "{:?}", backend);
+    }
+
+    std::fs::remove_file(&path).unwrap();
+}
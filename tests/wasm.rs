@@ -0,0 +1,220 @@
+mod common;
+
+use std::time::Duration;
+
+use bfdbg::{
+    executor::{
+        cell_type::CellType, eof_policy::EofPolicy, executor_config::ExecutorConfig,
+        io_mode::IoMode, optimized_command::optimize, tape::TapeMode,
+    },
+    parsing_src::translate_into_commands,
+    wasm::compiler::{
+        IMPORT_MODULE, INPUT_FUNCTION, OUTPUT_FUNCTION, RUN_FUNCTION, compile, status_error,
+    },
+};
+use common::{CELL_TYPES, Outcome, PROGRAMS, TAPES, XorShift, interpret};
+use wasmi::{Caller, Engine, Linker, Module, Store};
+
+/// The input and output of a running module.
+struct Host {
+    input: Vec<u8>,
+    read: usize,
+    output: Vec<u8>,
+}
+
+fn run_module(source: &str, config: ExecutorConfig, input: &[u8]) -> Outcome {
+    let (commands, _) = translate_into_commands(source, false).unwrap();
+    let binary = compile(&optimize(&commands, &config), &config)
+        .unwrap()
+        .to_binary();
+
+    let engine = Engine::default();
+    let module = Module::new(&engine, &binary[..]).unwrap();
+    let mut store = Store::new(
+        &engine,
+        Host {
+            input: input.to_vec(),
+            read: 0,
+            output: Vec::new(),
+        },
+    );
+    let mut linker = Linker::new(&engine);
+    linker
+        .func_wrap(
+            IMPORT_MODULE,
+            INPUT_FUNCTION,
+            |mut caller: Caller<'_, Host>| -> i32 {
+                let host = caller.data_mut();
+                match host.input.get(host.read) {
+                    Some(byte) => {
+                        host.read += 1;
+                        *byte as i32
+                    }
+                    None => -1,
+                }
+            },
+        )
+        .unwrap();
+    linker
+        .func_wrap(
+            IMPORT_MODULE,
+            OUTPUT_FUNCTION,
+            |mut caller: Caller<'_, Host>, byte: i32| {
+                caller.data_mut().output.push(byte as u8);
+            },
+        )
+        .unwrap();
+
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .unwrap()
+        .start(&mut store)
+        .unwrap();
+    let status = instance
+        .get_typed_func::<(), i32>(&store, RUN_FUNCTION)
+        .unwrap()
+        .call(&mut store, ())
+        .unwrap();
+
+    Outcome {
+        output: store.into_data().output,
+        result: status_error(status).map_or(Ok(()), Err),
+        context: None,
+    }
+}
+
+fn assert_same_behaviour(source: &str, config: ExecutorConfig, input: &[u8]) {
+    if let Some(expected) = interpret(source, config, input) {
+        // Modules do not tell where the program failed.
+        let expected = Outcome {
+            context: None,
+            ..expected
+        };
+        assert_eq!(
+            run_module(source, config, input),
+            expected,
+            "{} with {:?}",
+            source,
+            config
+        );
+    }
+}
+
+fn configs() -> impl Iterator<Item = ExecutorConfig> {
+    CELL_TYPES.into_iter().flat_map(|cell_type| {
+        TAPES
+            .into_iter()
+            .map(move |(tape_mode, tape_length)| ExecutorConfig {
+                cell_type,
+                tape_mode,
+                tape_length,
+                io_mode: IoMode::Byte,
+                ..ExecutorConfig::default()
+            })
+    })
+}
+
+#[test]
+fn programs_behave_like_the_interpreter() {
+    for config in configs() {
+        for program in PROGRAMS {
+            assert_same_behaviour(program, config, b"");
+        }
+    }
+}
+
+#[test]
+fn input_behaves_like_the_interpreter() {
+    let inputs: [&[u8]; 4] = [b"", b"a", b"hello\n", &[0, 1, 127, 128, 255]];
+    let programs = [",[.,]", ",.,.,.,.,.", ",>,>,<<.>.>.", ",[->+<]>."];
+
+    for config in configs() {
+        for eof_policy in [
+            EofPolicy::Zero,
+            EofPolicy::MinusOne,
+            EofPolicy::Unchanged,
+            EofPolicy::Error,
+        ] {
+            let config = ExecutorConfig {
+                eof_policy,
+                ..config
+            };
+            for program in programs {
+                for input in inputs {
+                    assert_same_behaviour(program, config, input);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn random_programs_behave_like_the_interpreter() {
+    let mut random = XorShift(0x9e3779b97f4a7c15);
+
+    for _ in 0..3000 {
+        let program = random.program(40);
+        let input = random.bytes(4);
+        let (tape_mode, tape_length) = TAPES[random.below(TAPES.len())];
+        let config = ExecutorConfig {
+            cell_type: CELL_TYPES[random.below(CELL_TYPES.len())],
+            tape_mode,
+            tape_length,
+            eof_policy: EofPolicy::Zero,
+            io_mode: IoMode::Byte,
+            ..ExecutorConfig::default()
+        };
+        assert_same_behaviour(&program, config, &input);
+    }
+}
+
+#[test]
+fn text_and_binary_modules_match() {
+    let (commands, _) = translate_into_commands(common::HELLO_WORLD, false).unwrap();
+    let config = ExecutorConfig {
+        cell_type: CellType::U8,
+        io_mode: IoMode::Byte,
+        ..ExecutorConfig::default()
+    };
+    let module = compile(&optimize(&commands, &config), &config).unwrap();
+
+    let text = module.to_text();
+    assert!(text.starts_with("(module"));
+    for name in [INPUT_FUNCTION, OUTPUT_FUNCTION, RUN_FUNCTION] {
+        assert!(text.contains(&format!("\"{}\"", name)), "{}", name);
+    }
+    assert!(Module::new(&Engine::default(), &module.to_binary()[..]).is_ok());
+}
+
+#[test]
+fn unsupported_configurations_are_rejected() {
+    let (commands, _) = translate_into_commands("+", false).unwrap();
+
+    for config in [
+        ExecutorConfig::default(),
+        ExecutorConfig {
+            cell_type: CellType::U8,
+            ..ExecutorConfig::default()
+        },
+        ExecutorConfig {
+            cell_type: CellType::U8,
+            io_mode: IoMode::Byte,
+            tape_mode: TapeMode::Bidirectional,
+            ..ExecutorConfig::default()
+        },
+        ExecutorConfig {
+            cell_type: CellType::U8,
+            io_mode: IoMode::Byte,
+            max_steps: Some(10),
+            ..ExecutorConfig::default()
+        },
+        ExecutorConfig {
+            cell_type: CellType::U8,
+            io_mode: IoMode::Byte,
+            timeout: Some(Duration::from_secs(1)),
+            ..ExecutorConfig::default()
+        },
+    ] {
+        assert!(compile(&optimize(&commands, &config), &config).is_err());
+    }
+}